@@ -1,13 +1,60 @@
-use ark_vesta::VestaConfig as Config;
+use std::ops::{Add, Mul};
+
+use ark_ec::short_weierstrass::Projective;
+use ark_serialize::CanonicalSerialize;
+use ark_vesta::{Fr, VestaConfig as Config};
 use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
-use rand::thread_rng;
+use rand::{Rng, thread_rng};
+use rayon::prelude::*;
 
-use fde::blind_fse::signer::BFDESigner;
-use fde::blind_fse::verifier::BFDEVerifier;
+use fde::blind_fse::signer::{BFDESigner, BFDESignerSecondRoundMessage, BFDESignerSecretRandomness};
+use fde::blind_fse::signer::BFDESignerFirstRoundMessage;
+use fde::blind_fse::verifier::{BFDEVerifier, BFDEVerifierFirstRoundMessage, BFDEVerifierSecretRandomness};
+use fde::hash::Hash256;
 use fde::schnorr_signature::key::{generate_key_pair, PublicKey, SecretKey};
 use fde::schnorr_signature::signer::Signer;
+use fde::schnorr_signature::util::group_element_into_bytes;
 use fde::schnorr_signature::verifier::Verifier;
 
+/// Round three in the previous format, which also sent g^{s_i} for every index
+fn legacy_second_round<R: Rng>(signer: &BFDESigner<Config>,
+                               secret_randomness: BFDESignerSecretRandomness<Config>,
+                               m1: &BFDESignerFirstRoundMessage<Config>,
+                               m2: &BFDEVerifierFirstRoundMessage<Config>,
+                               rng: &mut R,
+) -> (BFDESignerSecondRoundMessage<Config>, Vec<Projective<Config>>) {
+    let (_, m3) = signer.second_round(secret_randomness, m2, rng).unwrap();
+
+    // g^{s_i} = R_i * pk^{c_i}
+    let pk = signer.g.mul(signer.sk.sk);
+    let r_g = m1.answered(&m2.b);
+    let com = r_g.par_iter().zip(&m2.c).map(|(r_g_i, c_i)| r_g_i.add(pk.mul(c_i))).collect();
+
+    (m3, com)
+}
+
+/// Challenges as the verifier computed them before, with g^{alpha} * pk^{beta} done from scratch
+/// for every index and every R' normalized and every message cloned on its own
+fn legacy_challenges(verifier: &BFDEVerifier<Config>,
+                     m1: &BFDESignerFirstRoundMessage<Config>,
+                     m2: &BFDEVerifierFirstRoundMessage<Config>,
+                     message: &[Vec<u8>],
+                     secret_randomness: &BFDEVerifierSecretRandomness<Config>,
+) -> Vec<Fr> {
+    let r_g = m1.answered(&m2.b);
+    let (alpha, beta) = (&secret_randomness.alpha, &secret_randomness.beta);
+    (0..verifier.n).into_par_iter().map(|i| {
+        let r_g_prime = verifier.g.mul(alpha[i]).add(verifier.pk.pk.mul(beta[i])).add(r_g[i]);
+        let mut bytes = group_element_into_bytes::<Config>(&r_g_prime);
+        bytes.extend(message[i].clone());
+        Hash256::hash_bytes::<Fr>(bytes.as_slice()) + beta[i]
+    }).collect()
+}
+
+fn message_size(m3: &BFDESignerSecondRoundMessage<Config>) -> usize {
+    m3.com_k.compressed_size() + m3.alpha.compressed_size() + m3.opened.compressed_size()
+}
+
 fn benchmark_schnorr_signature(c: &mut Criterion) {
     let (sk, pk): (SecretKey<Config>, PublicKey<Config>) = generate_key_pair(&mut thread_rng());
 
@@ -19,19 +66,42 @@ fn benchmark_schnorr_signature(c: &mut Criterion) {
         let message = vec![[0u8, 1u8, 2u8, 3u8].to_vec(); message_len];
 
         // Blind signer/verifier
-        let fde_signer = BFDESigner::new(&signer, message_len);
-        let fde_verifier = BFDEVerifier::new(&verifier, message_len);
+        let fde_signer = BFDESigner::new(&signer, message_len, 1);
+        let fde_verifier = BFDEVerifier::new(&verifier, message_len, 1);
+
+        // Round three size, with and without the com vector
+        {
+            let (signer_secret_randomness, m1) = fde_signer.first_round(&mut thread_rng());
+            let (_, m2) = fde_verifier.first_round(&m1, &message, &mut thread_rng()).unwrap();
+            let (m3, com) = legacy_second_round(&fde_signer, signer_secret_randomness, &m1, &m2, &mut thread_rng());
+            let size = message_size(&m3);
+            println!("bfse round three, n = {}: {} bytes, {} bytes in the legacy format", message_len, size, size + com.compressed_size());
+        }
 
         // Benchmark fde_signer.second_round
         c.bench_with_input(
             BenchmarkId::new("bfse_signer", message_len),
             &message_len,
             |b, _| {
-                let (signer_secret_randomness, m1) = fde_signer.first_round(&mut thread_rng());
-                let (_, m2) = fde_verifier.first_round(&m1, &message, &mut thread_rng());
+                let (_, m1) = fde_signer.first_round(&mut thread_rng());
+                let (_, m2) = fde_verifier.first_round(&m1, &message, &mut thread_rng()).unwrap();
+                b.iter(|| {
+                    let (signer_secret_randomness, _) = fde_signer.first_round(&mut thread_rng());
+                    fde_signer.second_round(signer_secret_randomness, &m2, &mut thread_rng()).unwrap();
+                });
+            },
+        );
+
+        // Same, sending the com vector as before
+        c.bench_with_input(
+            BenchmarkId::new("bfse_signer_legacy", message_len),
+            &message_len,
+            |b, _| {
+                let (_, m1) = fde_signer.first_round(&mut thread_rng());
+                let (_, m2) = fde_verifier.first_round(&m1, &message, &mut thread_rng()).unwrap();
                 b.iter(|| {
-                    let (_, _) = fde_signer.first_round(&mut thread_rng());
-                    fde_signer.second_round(&signer_secret_randomness, &m2, &mut thread_rng());
+                    let (signer_secret_randomness, _) = fde_signer.first_round(&mut thread_rng());
+                    legacy_second_round(&fde_signer, signer_secret_randomness, &m1, &m2, &mut thread_rng());
                 });
             },
         );
@@ -42,10 +112,10 @@ fn benchmark_schnorr_signature(c: &mut Criterion) {
             &message_len,
             |b, _| {
                 let (signer_secret_randomness, m1) = fde_signer.first_round(&mut thread_rng());
-                let (_, m2) = fde_verifier.first_round(&m1, &message, &mut thread_rng());
-                let m3 = fde_signer.second_round(&signer_secret_randomness, &m2, &mut thread_rng());
+                let (_, m2) = fde_verifier.first_round(&m1, &message, &mut thread_rng()).unwrap();
+                let (_, m3) = fde_signer.second_round(signer_secret_randomness, &m2, &mut thread_rng()).unwrap();
                 b.iter(|| {
-                    let (_, _) = fde_verifier.first_round(&m1, &message, &mut thread_rng());
+                    let (_, _) = fde_verifier.first_round(&m1, &message, &mut thread_rng()).unwrap();
                     fde_verifier.second_round(&m1, &m2, &m3);
                 });
             },
@@ -53,6 +123,36 @@ fn benchmark_schnorr_signature(c: &mut Criterion) {
     }
 }
 
+fn benchmark_verifier_first_round(c: &mut Criterion) {
+    let (sk, pk): (SecretKey<Config>, PublicKey<Config>) = generate_key_pair(&mut thread_rng());
+    let message_len = 1024;
+    let message = vec![[0u8, 1u8, 2u8, 3u8].to_vec(); message_len];
+
+    let fde_signer = BFDESigner::new(&Signer::new(sk), message_len, 1);
+    let fde_verifier = BFDEVerifier::new(&Verifier::new(pk), message_len, 1);
+    let (_, m1) = fde_signer.first_round(&mut thread_rng());
+
+    // both ways of computing the challenges agree
+    let (verifier_secret_randomness, m2) = fde_verifier.first_round(&m1, &message, &mut thread_rng()).unwrap();
+    assert!(legacy_challenges(&fde_verifier, &m1, &m2, &message, &verifier_secret_randomness) == m2.c);
+
+    c.bench_with_input(
+        BenchmarkId::new("bfse_verifier_first_round", message_len),
+        &message_len,
+        |b, _| {
+            b.iter(|| fde_verifier.first_round(&m1, &message, &mut thread_rng()));
+        },
+    );
+
+    c.bench_with_input(
+        BenchmarkId::new("bfse_verifier_first_round_legacy", message_len),
+        &message_len,
+        |b, _| {
+            b.iter(|| legacy_challenges(&fde_verifier, &m1, &m2, &message, &verifier_secret_randomness));
+        },
+    );
+}
+
 fn custom_criterion_config() -> Criterion {
     Criterion::default().sample_size(25)
 }
@@ -61,7 +161,7 @@ fn custom_criterion_config() -> Criterion {
 criterion_group! {
     name = bench_fde;
     config = custom_criterion_config();
-    targets =  benchmark_schnorr_signature
+    targets =  benchmark_schnorr_signature, benchmark_verifier_first_round
 }
 
 criterion_main!(bench_fde);
@@ -31,7 +31,7 @@ fn benchmark_schnorr_signature(c: &mut Criterion) {
                 let  (vec_alpha, r_g, g_k, k) = FSE::sign(&sk, &message, &mut thread_rng());
                 b.iter(|| {
                     FSE::verify(&pk, &message, &vec_alpha, &r_g, &g_k);
-                    let signatures = FSE::recover(&vec_alpha, &r_g, k);
+                    let _ = FSE::recover(&pk, &message, &vec_alpha, &r_g, &g_k, k);
                 });
             },
         );
@@ -1,4 +1,5 @@
 pub mod verifier;
 
 pub mod signer;
+pub mod session;
 mod test;
\ No newline at end of file
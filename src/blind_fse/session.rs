@@ -0,0 +1,154 @@
+use ark_ec::CurveConfig;
+use ark_ec::short_weierstrass::{Projective, SWCurveConfig};
+use ark_ff::PrimeField;
+use rand::Rng;
+
+use crate::blind_fse::signer::{BFDESigner, BFDESignerFirstRoundMessage, BFDESignerSecondRoundMessage, BFDESignerSecretRandomness};
+use crate::blind_fse::verifier::{BFDEVerifier, BFDEVerifierFirstRoundMessage, BFDEVerifierSecretRandomness};
+use crate::fse::error::FseError;
+use crate::schnorr_signature::signature::Signature;
+use crate::schnorr_signature::util::erase_scalar;
+
+/// Signer session that has sent the nonce commitments and waits for the challenges.
+/// Answering consumes the session, so the nonces can never answer two sets of challenges
+pub struct BFDESignerAwaitingChallenge<'a, G1>
+where
+    G1: SWCurveConfig + Clone,
+    G1::ScalarField: PrimeField,
+{
+    signer: &'a BFDESigner<G1>,
+    secret_randomness: BFDESignerSecretRandomness<G1>,
+}
+
+/// Signer session that has answered and holds k until it is paid, k is erased if the
+/// session is dropped instead
+pub struct BFDESignerAwaitingPayment<G1>
+where
+    G1: SWCurveConfig + Clone,
+    G1::ScalarField: PrimeField,
+{
+    k: G1::ScalarField,
+}
+
+impl<G1> Drop for BFDESignerAwaitingPayment<G1>
+where
+    G1: SWCurveConfig + Clone,
+    G1::ScalarField: PrimeField,
+{
+    fn drop(&mut self) {
+        erase_scalar(&mut self.k);
+    }
+}
+
+/// Verifier session that has sent the challenges and waits for the signer's answer
+pub struct BFDEVerifierAwaitingResponse<'a, G1>
+where
+    G1: SWCurveConfig + Clone,
+    G1::ScalarField: PrimeField,
+{
+    verifier: &'a BFDEVerifier<G1>,
+    secret_randomness: BFDEVerifierSecretRandomness<G1>,
+    message: Vec<Vec<u8>>,
+    m1: BFDESignerFirstRoundMessage<G1>,
+    m2: BFDEVerifierFirstRoundMessage<G1>,
+}
+
+/// Verifier session whose answer checked out, waiting for k to recover the signatures
+pub struct BFDEVerifierAwaitingKey<'a, G1>
+where
+    G1: SWCurveConfig + Clone,
+    G1::ScalarField: PrimeField,
+{
+    session: BFDEVerifierAwaitingResponse<'a, G1>,
+    m3: BFDESignerSecondRoundMessage<G1>,
+}
+
+impl<G1> BFDESigner<G1>
+where
+    G1: SWCurveConfig + Clone,
+    G1::ScalarField: PrimeField,
+{
+    pub fn open_session<R: Rng>(&self, rng: &mut R) -> (BFDESignerAwaitingChallenge<'_, G1>, BFDESignerFirstRoundMessage<G1>) {
+        let (secret_randomness, m1) = self.first_round(rng);
+        (BFDESignerAwaitingChallenge { signer: self, secret_randomness }, m1)
+    }
+}
+
+impl<G1> BFDESignerAwaitingChallenge<'_, G1>
+where
+    G1: SWCurveConfig + Clone,
+    G1::ScalarField: PrimeField,
+{
+    /// A malformed challenge ends the session, the nonces are not kept for another try
+    pub fn respond<R: Rng>(self, m2: &BFDEVerifierFirstRoundMessage<G1>, rng: &mut R) -> Result<(BFDESignerAwaitingPayment<G1>, BFDESignerSecondRoundMessage<G1>), FseError> {
+        let (k, m3) = self.signer.second_round(self.secret_randomness, m2, rng)?;
+        Ok((BFDESignerAwaitingPayment::new(k), m3))
+    }
+}
+
+impl<G1> BFDESignerAwaitingPayment<G1>
+where
+    G1: SWCurveConfig + Clone,
+    G1::ScalarField: PrimeField,
+{
+    pub(crate) fn new(k: G1::ScalarField) -> Self {
+        BFDESignerAwaitingPayment { k }
+    }
+
+    pub fn reveal_key(self) -> G1::ScalarField {
+        self.k
+    }
+}
+
+impl<G1> BFDEVerifier<G1>
+where
+    G1: SWCurveConfig + Clone,
+    G1::ScalarField: PrimeField,
+{
+    #[allow(clippy::type_complexity)]
+    pub fn open_session<R: Rng>(&self,
+                                m1: BFDESignerFirstRoundMessage<G1>,
+                                message: Vec<Vec<u8>>,
+                                rng: &mut R,
+    ) -> Result<(BFDEVerifierAwaitingResponse<'_, G1>, BFDEVerifierFirstRoundMessage<G1>), FseError>
+    where
+        <G1 as CurveConfig>::BaseField: PrimeField,
+    {
+        let (secret_randomness, m2) = self.first_round(&m1, &message, rng)?;
+        let sent = m2.clone();
+        Ok((BFDEVerifierAwaitingResponse { verifier: self, secret_randomness, message, m1, m2 }, sent))
+    }
+}
+
+impl<'a, G1> BFDEVerifierAwaitingResponse<'a, G1>
+where
+    G1: SWCurveConfig + Clone,
+    G1::ScalarField: PrimeField,
+{
+    /// Fails with InvalidResponse, ending the session, if the answer does not check out
+    pub fn receive(self, m3: BFDESignerSecondRoundMessage<G1>) -> Result<BFDEVerifierAwaitingKey<'a, G1>, FseError> {
+        if !self.verifier.second_round(&self.m1, &self.m2, &m3) {
+            return Err(FseError::InvalidResponse);
+        }
+        Ok(BFDEVerifierAwaitingKey { session: self, m3 })
+    }
+}
+
+impl<G1> BFDEVerifierAwaitingKey<'_, G1>
+where
+    G1: SWCurveConfig + Clone,
+    G1::ScalarField: PrimeField,
+{
+    /// The commitment to k the payment is locked to
+    pub fn com_k(&self) -> Projective<G1> {
+        self.m3.com_k
+    }
+
+    pub fn recover(self, k: G1::ScalarField) -> Result<Vec<Signature<G1>>, FseError>
+    where
+        <G1 as CurveConfig>::BaseField: PrimeField,
+    {
+        let session = self.session;
+        session.verifier.recover(&session.secret_randomness, &session.m1, &session.m2, &self.m3, &session.message, k)
+    }
+}
@@ -1,16 +1,24 @@
-use std::ops::Mul;
+use std::ops::{Add, Mul};
 
 use ark_ec::short_weierstrass::{Projective, SWCurveConfig};
-use ark_ff::PrimeField;
+use ark_ff::{PrimeField, Zero};
 use ark_std::UniformRand;
 use rand::Rng;
 use rayon::iter::ParallelIterator;
 use rayon::prelude::{IntoParallelIterator, IntoParallelRefIterator};
 
 use crate::blind_fse::verifier::BFDEVerifierFirstRoundMessage;
+use crate::fse::error::FseError;
 use crate::schnorr_signature::key::SecretKey;
 use crate::schnorr_signature::signer::Signer;
-
+use crate::schnorr_signature::util::erase_scalar;
+
+/// Blind FSE signer running `lambda` parallel cut-and-choose repetitions:
+/// every repetition commits to two branches, the verifier opens one and keeps the other closed.
+/// The nonce answered for message i is the product of the closed branches of all repetitions,
+/// so a signer that can open only one branch per repetition passes every opening with
+/// probability 2^-lambda. A single answer per message is sent, so one k unmasks exactly
+/// n signatures
 #[derive(Clone, Debug, Default)]
 pub struct BFDESigner<G1>
 where
@@ -20,16 +28,48 @@ where
     pub sk: SecretKey<G1>,
     pub g: Projective<G1>,
     pub n: usize,
+    pub lambda: usize,
 }
 
-#[derive(Clone, Debug, Default)]
+/// Nonces indexed by [repetition][message], consumed by the second round so they cannot
+/// answer two sets of challenges:
+///
+/// ```compile_fail,E0382
+/// use ark_bn254::g1::Config;
+/// use fde::blind_fse::signer::BFDESigner;
+/// use fde::blind_fse::verifier::BFDEVerifier;
+/// use fde::schnorr_signature::key::{generate_key_pair, PublicKey, SecretKey};
+/// use fde::schnorr_signature::signer::Signer;
+/// use fde::schnorr_signature::verifier::Verifier;
+/// use rand::thread_rng;
+///
+/// let (sk, pk): (SecretKey<Config>, PublicKey<Config>) = generate_key_pair(&mut thread_rng());
+/// let fde_signer = BFDESigner::new(&Signer::new(sk), 1, 4);
+/// let fde_verifier = BFDEVerifier::new(&Verifier::new(pk), 1, 4);
+///
+/// let (secret_randomness, m1) = fde_signer.first_round(&mut thread_rng());
+/// let (_, m2_a) = fde_verifier.first_round(&m1, &[vec![0u8]], &mut thread_rng()).unwrap();
+/// let (_, m2_b) = fde_verifier.first_round(&m1, &[vec![1u8]], &mut thread_rng()).unwrap();
+/// fde_signer.second_round(secret_randomness, &m2_a, &mut thread_rng()).unwrap();
+/// fde_signer.second_round(secret_randomness, &m2_b, &mut thread_rng()).unwrap();
+/// ```
 pub struct BFDESignerSecretRandomness<G1>
 where
     G1: SWCurveConfig + Clone,
     G1::ScalarField: PrimeField,
 {
-    pub r0: Vec<G1::ScalarField>,
-    pub r1: Vec<G1::ScalarField>,
+    r0: Vec<Vec<G1::ScalarField>>,
+    r1: Vec<Vec<G1::ScalarField>>,
+}
+
+impl<G1> Drop for BFDESignerSecretRandomness<G1>
+where
+    G1: SWCurveConfig + Clone,
+    G1::ScalarField: PrimeField,
+{
+    fn drop(&mut self) {
+        self.r0.iter_mut().chain(self.r1.iter_mut()).flatten().for_each(erase_scalar);
+    }
 }
 
 #[derive(Clone, Debug, Default)]
@@ -38,10 +78,29 @@ where
     G1: SWCurveConfig + Clone,
     G1::ScalarField: PrimeField,
 {
-    pub r0_g: Vec<Projective<G1>>,
-    pub r1_g: Vec<Projective<G1>>,
+    pub r0_g: Vec<Vec<Projective<G1>>>,
+    pub r1_g: Vec<Vec<Projective<G1>>>,
 }
 
+impl<G1> BFDESignerFirstRoundMessage<G1>
+where
+    G1: SWCurveConfig + Clone,
+    G1::ScalarField: PrimeField,
+{
+    /// R_i = prod_j R^{(j)}_{b_j, i}, the nonce commitments answered for the verifier's choice
+    /// of closed branches. Both branches of every repetition must cover the same messages
+    pub fn answered(&self, b: &[bool]) -> Vec<Projective<G1>> {
+        let n = self.r0_g.first().map_or(0, Vec::len);
+        (0..n).into_par_iter().map(|i| {
+            b.iter().enumerate().fold(Projective::zero(), |acc, (j, b_j)| {
+                acc.add(if *b_j { self.r1_g[j][i] } else { self.r0_g[j][i] })
+            })
+        }).collect()
+    }
+}
+
+/// Response on the closed branches, and the nonces of the opened branch of every repetition.
+/// g^{s_i} is not sent, the verifier derives it as R_i * pk^{c_i}
 #[derive(Clone, Debug, Default)]
 pub struct BFDESignerSecondRoundMessage<G1>
 where
@@ -50,8 +109,7 @@ where
 {
     pub com_k: Projective<G1>,
     pub alpha: Vec<G1::ScalarField>,
-    pub com: Vec<Projective<G1>>,
-    pub b: bool,
+    pub opened: Vec<Vec<G1::ScalarField>>,
 }
 
 impl<G1> BFDESigner<G1>
@@ -59,70 +117,71 @@ where
     G1: SWCurveConfig + Clone,
     G1::ScalarField: PrimeField,
 {
-    pub fn new(signer: &Signer<G1>, n: usize) -> BFDESigner<G1> {
+    /// Signer for n messages with soundness error 2^-lambda
+    pub fn new(signer: &Signer<G1>, n: usize, lambda: usize) -> BFDESigner<G1> {
         BFDESigner {
             sk: signer.get_secret_key(),
             g: signer.get_generator(),
             n,
+            lambda,
         }
     }
 
     pub fn first_round<R: Rng>(&self, rng: &mut R) -> (BFDESignerSecretRandomness<G1>, BFDESignerFirstRoundMessage<G1>) {
         // Sequential random generation (since rng is not thread-safe)
-        let r0: Vec<G1::ScalarField> = (0..self.n).map(|_| G1::ScalarField::rand(rng)).collect();
-        let r1: Vec<G1::ScalarField> = (0..self.n).map(|_| G1::ScalarField::rand(rng)).collect();
+        let mut nonces = |_| (0..self.n).map(|_| G1::ScalarField::rand(rng)).collect::<Vec<G1::ScalarField>>();
+        let r0: Vec<Vec<G1::ScalarField>> = (0..self.lambda).map(&mut nonces).collect();
+        let r1: Vec<Vec<G1::ScalarField>> = (0..self.lambda).map(&mut nonces).collect();
 
         // Parallel scalar multiplication using rayon
-        let r0_g: Vec<Projective<G1>> = r0.par_iter().map(|r_i| self.g.mul(*r_i)).collect();
-        let r1_g: Vec<Projective<G1>> = r1.par_iter().map(|r_i| self.g.mul(*r_i)).collect();
+        let commit = |r: &Vec<Vec<G1::ScalarField>>| -> Vec<Vec<Projective<G1>>> {
+            r.iter().map(|r_j| r_j.par_iter().map(|r_i| self.g.mul(*r_i)).collect()).collect()
+        };
+        let r0_g = commit(&r0);
+        let r1_g = commit(&r1);
 
         // Return the tuple of (r, g^r)
         (BFDESignerSecretRandomness { r0, r1 }, BFDESignerFirstRoundMessage { r0_g, r1_g })
     }
 
+    /// Returns k, to be revealed once paid, together with the second round message.
+    /// Fails with LengthMismatch unless there is a branch for every repetition and a single
+    /// challenge per message
     pub fn second_round<R: Rng>(&self,
-                                secret_randomness: &BFDESignerSecretRandomness<G1>,
+                                secret_randomness: BFDESignerSecretRandomness<G1>,
                                 m1: &BFDEVerifierFirstRoundMessage<G1>,
                                 rng: &mut R,
-    ) -> BFDESignerSecondRoundMessage<G1> {
-        let b = bool::rand(rng);
+    ) -> Result<(G1::ScalarField, BFDESignerSecondRoundMessage<G1>), FseError> {
+        if self.lambda == 0 || m1.b.len() != self.lambda || m1.c.len() != self.n {
+            return Err(FseError::LengthMismatch);
+        }
 
         // Random generation is kept sequential
         let k = G1::ScalarField::rand(rng);
         let com_k: Projective<G1> = self.g.mul(k);
 
-        let (r, c) = {
-            if b == true {
-                (&secret_randomness.r1, &m1.c1)
-            } else {
-                (&secret_randomness.r0, &m1.c0)
-            }
-        };
+        // answer with r_i = sum_j r^{(j)}_{b_j, i}, the closed branches of every repetition
+        let closed = |j: usize| if m1.b[j] { &secret_randomness.r1[j] } else { &secret_randomness.r0[j] };
 
-        // Parallelize vec_s computation
-        let vec_s: Vec<G1::ScalarField> = (0..r.len())
+        // Parallelize alpha computation ((s + k) / 2)
+        let alpha: Vec<G1::ScalarField> = (0..self.n)
             .into_par_iter()
-            .map(|i| r[i] + c[i] * self.sk.sk)
-            .collect();
-
-        // Parallelize vec_g_s computation (g^s)
-        let vec_g_s: Vec<Projective<G1>> = vec_s
-            .par_iter()
-            .map(|s| self.g.mul(*s))
+            .map(|i| {
+                let r_i = (0..self.lambda).fold(G1::ScalarField::zero(), |acc, j| acc + closed(j)[i]);
+                (r_i + m1.c[i] * self.sk.sk + k) / G1::ScalarField::from(2u8)
+            })
             .collect();
 
-        // Parallelize alpha computation ((s + k) / 2)
-        let alpha: Vec<G1::ScalarField> = vec_s
-            .par_iter()
-            .map(|s| (*s + k) / G1::ScalarField::from(2u8))
-            .collect();
+        // open the other branch of every repetition
+        let opened = m1.b.iter().enumerate().map(|(j, b_j)| {
+            if *b_j { secret_randomness.r0[j].clone() } else { secret_randomness.r1[j].clone() }
+        }).collect();
 
-        // Return the second round message
-        BFDESignerSecondRoundMessage {
+        // Return k and the second round message
+        Ok((k, BFDESignerSecondRoundMessage {
             com_k,
             alpha,
-            com: vec_g_s,
-            b,
-        }
+            opened,
+        }))
     }
 }
@@ -1,9 +1,11 @@
 #[cfg(test)]
 mod tests {
+    use ark_bn254::Fr;
     use rand::thread_rng;
 
     use crate::blind_fse::signer::BFDESigner;
     use crate::blind_fse::verifier::BFDEVerifier;
+    use crate::fse::error::FseError;
     use crate::schnorr_signature::key::{generate_key_pair, PublicKey, SecretKey};
     use crate::schnorr_signature::signer::Signer;
     use crate::schnorr_signature::verifier::Verifier;
@@ -11,12 +13,10 @@ mod tests {
 
     #[test]
     fn test_schnorr_signature() {
-        let message = {
-            let mut temp = Vec::new();
-            temp.push([0u8, 1u8, 2u8, 3u8].to_vec());
-            temp.push([0u8, 1u8, 2u8, 3u8].to_vec());
-            temp
-        };
+        let message = vec![
+            [0u8, 1u8, 2u8, 3u8].to_vec(),
+            [0u8, 1u8, 2u8, 3u8].to_vec(),
+        ];
         let (sk, pk): (SecretKey<Config>, PublicKey<Config>) = generate_key_pair(&mut thread_rng());
 
         // non-blind signer/verifier
@@ -24,14 +24,188 @@ mod tests {
         let verifier = Verifier::new(pk);
 
         // blind signer/verifier
-        let fde_signer = BFDESigner::new(&signer, 2);
-        let fde_verifier = BFDEVerifier::new(&verifier, 2);
+        let fde_signer = BFDESigner::new(&signer, 2, 8);
+        let fde_verifier = BFDEVerifier::new(&verifier, 2, 8);
 
         // interaction
         let (signer_secret_randomness, m1) = fde_signer.first_round(&mut thread_rng());
-        let (_, m2) = fde_verifier.first_round(&m1, &message, &mut thread_rng());
-        let m3 = fde_signer.second_round(&signer_secret_randomness, &m2, &mut thread_rng());
+        let (_, m2) = fde_verifier.first_round(&m1, &message, &mut thread_rng()).unwrap();
+        let (_, m3) = fde_signer.second_round(signer_secret_randomness, &m2, &mut thread_rng()).unwrap();
 
         assert!(fde_verifier.second_round(&m1, &m2, &m3));
     }
+
+    #[test]
+    fn test_recover() {
+        let message = vec![
+            [0u8, 1u8, 2u8, 3u8].to_vec(),
+            [4u8, 5u8, 6u8, 7u8].to_vec(),
+            [8u8, 9u8, 10u8, 11u8].to_vec(),
+        ];
+        let (sk, pk): (SecretKey<Config>, PublicKey<Config>) = generate_key_pair(&mut thread_rng());
+        let signer = Signer::new(sk);
+        let verifier = Verifier::new(pk);
+
+        let fde_signer = BFDESigner::new(&signer, 3, 4);
+        let fde_verifier = BFDEVerifier::new(&verifier, 3, 4);
+
+        let (signer_secret_randomness, m1) = fde_signer.first_round(&mut thread_rng());
+        let (verifier_secret_randomness, m2) = fde_verifier.first_round(&m1, &message, &mut thread_rng()).unwrap();
+        let (k, m3) = fde_signer.second_round(signer_secret_randomness, &m2, &mut thread_rng()).unwrap();
+        assert!(fde_verifier.second_round(&m1, &m2, &m3));
+
+        // the signer reveals k once paid
+        let signatures = fde_verifier.recover(&verifier_secret_randomness, &m1, &m2, &m3, &message, k).unwrap();
+        for (m_i, sig) in message.iter().zip(&signatures) {
+            assert!(verifier.verify(m_i, sig));
+        }
+
+        // the unblinded signatures are not the ones the signer saw
+        let signer_r_g = m1.answered(&m2.b);
+        assert!(signatures.iter().zip(&signer_r_g).all(|(sig, r_g)| sig.r_g != *r_g));
+
+        assert!(matches!(fde_verifier.recover(&verifier_secret_randomness, &m1, &m2, &m3, &message, k + Fr::from(1u8)), Err(FseError::InvalidKey)));
+        assert!(matches!(fde_verifier.recover(&verifier_secret_randomness, &m1, &m2, &m3, &message[1..], k), Err(FseError::LengthMismatch)));
+    }
+
+    #[test]
+    fn test_session() {
+        let message = vec![[0u8, 1u8, 2u8, 3u8].to_vec(), [4u8, 5u8, 6u8, 7u8].to_vec()];
+        let (sk, pk): (SecretKey<Config>, PublicKey<Config>) = generate_key_pair(&mut thread_rng());
+        let signer = Signer::new(sk);
+        let verifier = Verifier::new(pk);
+
+        let fde_signer = BFDESigner::new(&signer, 2, 4);
+        let fde_verifier = BFDEVerifier::new(&verifier, 2, 4);
+
+        // every round consumes the previous state
+        let (signer_session, m1) = fde_signer.open_session(&mut thread_rng());
+        let (verifier_session, m2) = fde_verifier.open_session(m1, message.clone(), &mut thread_rng()).unwrap();
+        let (signer_session, m3) = signer_session.respond(&m2, &mut thread_rng()).unwrap();
+        let com_k = m3.com_k;
+        let verifier_session = verifier_session.receive(m3).unwrap();
+        assert_eq!(verifier_session.com_k(), com_k);
+
+        let signatures = verifier_session.recover(signer_session.reveal_key()).unwrap();
+        assert!(verifier.batch_verify(&message, &signatures));
+
+        // a tampered answer ends the session
+        let (signer_session, m1) = fde_signer.open_session(&mut thread_rng());
+        let (verifier_session, m2) = fde_verifier.open_session(m1, message, &mut thread_rng()).unwrap();
+        let (_, mut m3) = signer_session.respond(&m2, &mut thread_rng()).unwrap();
+        m3.alpha[0] += Fr::from(1u8);
+        assert!(matches!(verifier_session.receive(m3), Err(FseError::InvalidResponse)));
+    }
+
+    #[test]
+    fn test_cut_and_choose() {
+        let message = vec![[0u8, 1u8, 2u8, 3u8].to_vec(); 2];
+        let (sk, pk): (SecretKey<Config>, PublicKey<Config>) = generate_key_pair(&mut thread_rng());
+        let signer = Signer::new(sk);
+        let verifier = Verifier::new(pk);
+
+        let fde_signer = BFDESigner::new(&signer, 2, 16);
+        let fde_verifier = BFDEVerifier::new(&verifier, 2, 16);
+
+        // a signer answering on the branch of its choice instead of the verifier's
+        let (signer_secret_randomness, m1) = fde_signer.first_round(&mut thread_rng());
+        let (_, mut m2) = fde_verifier.first_round(&m1, &message, &mut thread_rng()).unwrap();
+        let honest = m2.clone();
+        m2.b.iter_mut().for_each(|b| *b = false);
+        let (_, m3) = fde_signer.second_round(signer_secret_randomness, &m2, &mut thread_rng()).unwrap();
+        assert!(honest.b.iter().all(|b| !b) || !fde_verifier.second_round(&m1, &honest, &m3));
+
+        // a branch committed to without knowing its nonce cannot be answered nor opened
+        let (signer_secret_randomness, m1) = fde_signer.first_round(&mut thread_rng());
+        let (_, m2) = fde_verifier.first_round(&m1, &message, &mut thread_rng()).unwrap();
+        let (_, m3) = fde_signer.second_round(signer_secret_randomness, &m2, &mut thread_rng()).unwrap();
+        assert!(fde_verifier.second_round(&m1, &m2, &m3));
+        let mut opened = m1.clone();
+        if m2.b[3] { opened.r0_g[3][1] = verifier.get_public_key() } else { opened.r1_g[3][1] = verifier.get_public_key() }
+        assert!(!fde_verifier.second_round(&opened, &m2, &m3));
+        // every repetition contributes its closed branch to the answered nonces
+        for j in [0, 7, 15] {
+            let mut answered = m1.clone();
+            if m2.b[j] { answered.r1_g[j][1] = verifier.get_public_key() } else { answered.r0_g[j][1] = verifier.get_public_key() }
+            assert!(!fde_verifier.second_round(&answered, &m2, &m3));
+        }
+
+        // the verifier expects exactly lambda repetitions
+        let short = BFDEVerifier::new(&verifier, 2, 15);
+        assert!(!short.second_round(&m1, &m2, &m3));
+    }
+
+    #[test]
+    fn test_unlinkability() {
+        let message: Vec<Vec<u8>> = (0u8..4u8).map(|i| [i, 1u8, 2u8, 3u8].to_vec()).collect();
+        let (sk, pk): (SecretKey<Config>, PublicKey<Config>) = generate_key_pair(&mut thread_rng());
+        let signer = Signer::new(sk);
+        let verifier = Verifier::new(pk);
+
+        let fde_signer = BFDESigner::new(&signer, 4, 1);
+        let fde_verifier = BFDEVerifier::new(&verifier, 4, 1);
+
+        let (signer_secret_randomness, m1) = fde_signer.first_round(&mut thread_rng());
+        let (verifier_secret_randomness, m2) = fde_verifier.first_round(&m1, &message, &mut thread_rng()).unwrap();
+        let (k, m3) = fde_signer.second_round(signer_secret_randomness, &m2, &mut thread_rng()).unwrap();
+        let signatures = fde_verifier.recover(&verifier_secret_randomness, &m1, &m2, &m3, &message, k).unwrap();
+
+        // with a shared blinding offset, R'_i - R_i would be the same for the whole batch
+        // and the signer could match its session to the published signatures
+        let signer_r_g = m1.answered(&m2.b);
+        let offsets: Vec<_> = signatures.iter().zip(&signer_r_g).map(|(sig, r_g)| sig.r_g - r_g).collect();
+        for i in 0..offsets.len() {
+            for j in i + 1..offsets.len() {
+                assert_ne!(offsets[i], offsets[j]);
+            }
+        }
+
+        // nor is there a common scalar offset between the signer's responses and the published s'
+        let s_offsets: Vec<Fr> = signatures.iter().zip(&m3.alpha)
+            .map(|(sig, alpha)| sig.s - (Fr::from(2u8) * alpha - k))
+            .collect();
+        assert!(s_offsets.windows(2).all(|w| w[0] != w[1]));
+    }
+
+    #[test]
+    fn test_single_batch() {
+        let message: Vec<Vec<u8>> = (0u8..2u8).map(|i| [i, 1u8, 2u8, 3u8].to_vec()).collect();
+        let other: Vec<Vec<u8>> = (0u8..2u8).map(|i| [i, 4u8, 5u8, 6u8].to_vec()).collect();
+        let (sk, pk): (SecretKey<Config>, PublicKey<Config>) = generate_key_pair(&mut thread_rng());
+        let signer = Signer::new(sk);
+        let verifier = Verifier::new(pk);
+
+        let fde_signer = BFDESigner::new(&signer, 2, 4);
+        let fde_verifier = BFDEVerifier::new(&verifier, 2, 4);
+
+        // a buyer blinding another message set for a second repetition is refused, so one
+        // payment never unlocks more than n signatures
+        let (signer_secret_randomness, m1) = fde_signer.first_round(&mut thread_rng());
+        let (_, mut m2) = fde_verifier.first_round(&m1, &message, &mut thread_rng()).unwrap();
+        let (_, m2_other) = fde_verifier.first_round(&m1, &other, &mut thread_rng()).unwrap();
+        m2.c.extend(m2_other.c);
+        assert_eq!(fde_signer.second_round(signer_secret_randomness, &m2, &mut thread_rng()).err(), Some(FseError::LengthMismatch));
+
+        // the honest answer holds one masked scalar per message
+        let (signer_secret_randomness, m1) = fde_signer.first_round(&mut thread_rng());
+        let (_, m2) = fde_verifier.first_round(&m1, &message, &mut thread_rng()).unwrap();
+        let (_, m3) = fde_signer.second_round(signer_secret_randomness, &m2, &mut thread_rng()).unwrap();
+        assert_eq!(m3.alpha.len(), 2);
+
+        // malformed messages are length errors rather than panics
+        let mut short = m2.clone();
+        short.b.pop();
+        assert_eq!(fde_signer.second_round(fde_signer.first_round(&mut thread_rng()).0, &short, &mut thread_rng()).err(), Some(FseError::LengthMismatch));
+        assert!(!fde_verifier.second_round(&m1, &short, &m3));
+
+        let mut truncated = m1.clone();
+        truncated.r1_g[2].pop();
+        assert!(matches!(fde_verifier.first_round(&truncated, &message, &mut thread_rng()), Err(FseError::LengthMismatch)));
+        assert!(!fde_verifier.second_round(&truncated, &m2, &m3));
+        assert!(matches!(fde_verifier.first_round(&m1, &message[1..], &mut thread_rng()), Err(FseError::LengthMismatch)));
+
+        let mut unopened = m3.clone();
+        unopened.opened[1].pop();
+        assert!(!fde_verifier.second_round(&m1, &m2, &unopened));
+    }
 }
@@ -1,18 +1,21 @@
 use std::ops::Add;
 use std::ops::Mul;
 
-use ark_ec::{CurveConfig, Group};
-use ark_ec::short_weierstrass::{Projective, SWCurveConfig};
+use ark_ec::{CurveConfig, CurveGroup, Group};
+use ark_ec::scalar_mul::fixed_base::FixedBase;
+use ark_ec::short_weierstrass::{Affine, Projective, SWCurveConfig};
 use ark_ff::PrimeField;
 use ark_std::UniformRand;
 use rand::Rng;
 use rayon::iter::ParallelIterator;
-use rayon::prelude::IntoParallelIterator;
+use rayon::prelude::{IndexedParallelIterator, IntoParallelIterator, IntoParallelRefIterator};
 
 use crate::blind_fse::signer::{BFDESignerFirstRoundMessage, BFDESignerSecondRoundMessage};
+use crate::fse::error::FseError;
 use crate::hash::Hash256;
 use crate::schnorr_signature::key::PublicKey;
-use crate::schnorr_signature::util::group_element_into_bytes;
+use crate::schnorr_signature::signature::Signature;
+use crate::schnorr_signature::util::affine_into_bytes;
 use crate::schnorr_signature::verifier::Verifier;
 
 #[derive(Clone, Debug, Default)]
@@ -24,28 +27,42 @@ where
     pub pk: PublicKey<G1>,
     pub g: Projective<G1>,
     pub n: usize,
+    pub lambda: usize,
 }
 
+/// Blinding factors of the answered branch, independent for every message so that the
+/// unblinded R'_i do not share an offset the signer could link them by
 #[derive(Clone, Debug, Default)]
 pub struct BFDEVerifierSecretRandomness<G1>
 where
     G1: SWCurveConfig + Clone,
     G1::ScalarField: PrimeField,
 {
-    pub alpha_0: G1::ScalarField,
-    pub beta_0: G1::ScalarField,
-    pub alpha_1: G1::ScalarField,
-    pub beta_1: G1::ScalarField,
+    pub alpha: Vec<G1::ScalarField>,
+    pub beta: Vec<G1::ScalarField>,
 }
 
+/// Blinded challenges on the product of the closed branches, and in every repetition the
+/// branch that stays closed
 #[derive(Clone, Debug, Default)]
 pub struct BFDEVerifierFirstRoundMessage<G1>
 where
     G1: SWCurveConfig + Clone,
     G1::ScalarField: PrimeField,
 {
-    pub c0: Vec<G1::ScalarField>,
-    pub c1: Vec<G1::ScalarField>,
+    pub c: Vec<G1::ScalarField>,
+    pub b: Vec<bool>,
+}
+
+struct OffsetTables<G1>
+where
+    G1: SWCurveConfig + Clone,
+    G1::ScalarField: PrimeField,
+{
+    window: usize,
+    outerc: usize,
+    g: Vec<Vec<Affine<G1>>>,
+    pk: Vec<Vec<Affine<G1>>>,
 }
 
 impl<G1> BFDEVerifier<G1>
@@ -53,104 +70,166 @@ where
     G1: SWCurveConfig + Clone,
     G1::ScalarField: PrimeField,
 {
-    pub fn new(verifier: &Verifier<G1>, n: usize) -> BFDEVerifier<G1> {
+    /// Verifier for n messages with soundness error 2^-lambda
+    pub fn new(verifier: &Verifier<G1>, n: usize, lambda: usize) -> BFDEVerifier<G1> {
         BFDEVerifier {
             pk: verifier.pk.clone(),
             g: verifier.get_generator(),
             n,
+            lambda,
         }
     }
 
-    pub fn first_round<R: Rng>(&self,
-                               m1: &BFDESignerFirstRoundMessage<G1>,
-                               message: &Vec<Vec<u8>>,
-                               rng: &mut R,
-    ) -> (BFDEVerifierSecretRandomness<G1>, BFDEVerifierFirstRoundMessage<G1>)
+    /// Both branches of every repetition hold one nonce commitment per message
+    fn well_formed(&self, m1: &BFDESignerFirstRoundMessage<G1>) -> bool {
+        m1.r0_g.len() == self.lambda && m1.r1_g.len() == self.lambda
+            && m1.r0_g.iter().chain(&m1.r1_g).all(|r_g| r_g.len() == self.n)
+    }
+
+    /// Window tables of g and pk, computed once per first round and shared by every
+    /// index, the blinding factors differ per index but the bases do not
+    fn offset_tables(&self) -> OffsetTables<G1> {
+        let scalar_size = G1::ScalarField::MODULUS_BIT_SIZE as usize;
+        let window = FixedBase::get_mul_window_size(self.n);
+        OffsetTables {
+            window,
+            outerc: scalar_size.div_ceil(window),
+            g: FixedBase::get_window_table(scalar_size, window, self.g),
+            pk: FixedBase::get_window_table(scalar_size, window, self.pk.pk),
+        }
+    }
+
+    /// Blinded challenges of the answered branch: c_i = H(R_i * g^{alpha_i} * pk^{beta_i}, m_i) + beta_i
+    fn challenges(&self,
+                  tables: &OffsetTables<G1>,
+                  r_g: &[Projective<G1>],
+                  message: &[Vec<u8>],
+                  alpha: &[G1::ScalarField],
+                  beta: &[G1::ScalarField],
+    ) -> Vec<G1::ScalarField>
     where
         <G1 as CurveConfig>::BaseField: PrimeField,
     {
-        let alpha_0 = G1::ScalarField::rand(rng);
-        let beta_0 = G1::ScalarField::rand(rng);
-        let alpha_1 = G1::ScalarField::rand(rng);
-        let beta_1 = G1::ScalarField::rand(rng);
-
-        // Parallelized the computation of vec_c using rayon's par_iter
-        let vec_c0: Vec<G1::ScalarField> = (0..self.n).into_par_iter().map(|i| {
-            // R' = R * g^{alpha} * pk^{beta}
-            let r_g_prime: Projective<G1> = {
-                let mut temp = self.g.mul(alpha_0);
-                temp = temp.add(self.pk.pk.mul(beta_0));
-                temp = temp.add(m1.r0_g[i]);
-                temp
-            };
+        // R' = R * g^{alpha} * pk^{beta}
+        let r_g_prime: Vec<Projective<G1>> = (0..self.n).into_par_iter().map(|i| {
+            let alpha_g: Projective<G1> = FixedBase::windowed_mul(tables.outerc, tables.window, &tables.g, &alpha[i]);
+            let beta_pk: Projective<G1> = FixedBase::windowed_mul(tables.outerc, tables.window, &tables.pk, &beta[i]);
+            r_g[i].add(alpha_g).add(beta_pk)
+        }).collect();
 
+        // a single inversion for the whole branch instead of one per point
+        let r_g_prime = Projective::normalize_batch(&r_g_prime);
+
+        r_g_prime.par_iter().zip(message.par_iter()).zip(beta.par_iter()).map(|((r_g_prime_i, m_i), beta_i)| {
             let c_prime: G1::ScalarField = {
-                let mut bytes = group_element_into_bytes::<G1>(&r_g_prime);
-                bytes.extend(message[i].clone());
+                let mut bytes = affine_into_bytes::<G1>(r_g_prime_i);
+                bytes.extend_from_slice(m_i);
                 Hash256::hash_bytes(bytes.as_slice())
             };
 
-            let c = c_prime + beta_0;
-            c
-        }).collect();
+            c_prime + beta_i
+        }).collect()
+    }
 
-        // Parallelized the computation of vec_c using rayon's par_iter
-        let vec_c1: Vec<G1::ScalarField> = (0..self.n).into_par_iter().map(|i| {
-            // R' = R * g^{alpha} * pk^{beta}
-            let r_g_prime: Projective<G1> = {
-                let mut temp = self.g.mul(alpha_1);
-                temp = temp.add(self.pk.pk.mul(beta_1));
-                temp = temp.add(m1.r1_g[i]);
-                temp
-            };
+    /// Blind the messages under the product of the branches that are not opened,
+    /// fails with LengthMismatch if the signer's commitments do not cover lambda
+    /// repetitions of n messages
+    #[allow(clippy::type_complexity)]
+    pub fn first_round<R: Rng>(&self,
+                               m1: &BFDESignerFirstRoundMessage<G1>,
+                               message: &[Vec<u8>],
+                               rng: &mut R,
+    ) -> Result<(BFDEVerifierSecretRandomness<G1>, BFDEVerifierFirstRoundMessage<G1>), FseError>
+    where
+        <G1 as CurveConfig>::BaseField: PrimeField,
+    {
+        if self.lambda == 0 || message.len() != self.n || !self.well_formed(m1) {
+            return Err(FseError::LengthMismatch);
+        }
 
-            let c_prime: G1::ScalarField = {
-                let mut bytes = group_element_into_bytes::<G1>(&r_g_prime);
-                bytes.extend(message[i].clone());
-                Hash256::hash_bytes(bytes.as_slice())
-            };
+        let mut scalars = || (0..self.n).map(|_| G1::ScalarField::rand(rng)).collect::<Vec<G1::ScalarField>>();
+        let alpha = scalars();
+        let beta = scalars();
 
-            let c = c_prime + beta_1;
-            c
-        }).collect();
+        // the verifier picks which branch gets opened, after the signer committed to both
+        let b: Vec<bool> = (0..self.lambda).map(|_| bool::rand(rng)).collect();
 
+        let c = self.challenges(&self.offset_tables(), &m1.answered(&b), message, &alpha, &beta);
 
-        (BFDEVerifierSecretRandomness { alpha_0, beta_0, alpha_1, beta_1 }, BFDEVerifierFirstRoundMessage { c0: vec_c0, c1: vec_c1 })
+        Ok((BFDEVerifierSecretRandomness { alpha, beta }, BFDEVerifierFirstRoundMessage { c, b }))
     }
 
+    /// Check the answer on the closed branches and the opened branch of every repetition,
+    /// false if any of the messages does not have the expected shape
     pub fn second_round(&self,
                         m1: &BFDESignerFirstRoundMessage<G1>,
                         m2: &BFDEVerifierFirstRoundMessage<G1>,
                         m3: &BFDESignerSecondRoundMessage<G1>,
     ) -> bool
     {
-        let (r_g, c) = {
-            if m3.b == true {
-                (&m1.r1_g, &m2.c1)
-            } else {
-                (&m1.r0_g, &m2.c0)
-            }
-        };
-
-        // compute a vector of boolean and add them
-        let res: bool = (0..self.n).into_par_iter().map(|i| {
-            // com_i = R_i * pk^c_i
-            let temp = r_g[i].add(self.pk.pk.mul(c[i]));
-            let first_check = m3.com[i] == temp;
-
-            // g^a_i = (com_k * com_i)^{1/2}
-            let lhs: Projective<G1> = {
-                let temp = self.g.mul(m3.alpha[i]);
-                temp.double()
-            };
-            let rhs: Projective<G1> = m3.com_k.add(m3.com[i]);
-            let second_check = lhs == rhs;
+        let (lambda, n) = (self.lambda, self.n);
+        if lambda == 0 || !self.well_formed(m1) || m2.b.len() != lambda || m2.c.len() != n
+            || m3.alpha.len() != n || m3.opened.len() != lambda || m3.opened.iter().any(|opened| opened.len() != n) {
+            return false;
+        }
 
-            // Return the result of both checks
-            first_check && second_check
+        // g^a_i = (com_k * R_i * pk^c_i)^{1/2} on the closed branches
+        let r_g = m1.answered(&m2.b);
+        let answered = (0..n).into_par_iter().map(|i| {
+            let lhs: Projective<G1> = self.g.mul(m3.alpha[i]).double();
+            let rhs: Projective<G1> = m3.com_k.add(r_g[i]).add(self.pk.pk.mul(m2.c[i]));
+            lhs == rhs
         }).reduce(|| true, |acc, x| acc && x);
 
-        res
+        // the opened branches were committed to honestly
+        answered && (0..lambda).all(|j| {
+            let other_r_g = if m2.b[j] { &m1.r0_g[j] } else { &m1.r1_g[j] };
+            (0..n).into_par_iter()
+                .map(|i| self.g.mul(m3.opened[j][i]) == other_r_g[i])
+                .reduce(|| true, |acc, x| acc && x)
+        })
     }
-}
 
+    /// Unmask the answered nonces with the revealed k and unblind it:
+    /// R'_i = R_i * g^{alpha_i} * pk^{beta_i} and s'_i = 2 * a_i - k + alpha_i
+    pub fn recover(&self,
+                   secret_randomness: &BFDEVerifierSecretRandomness<G1>,
+                   m1: &BFDESignerFirstRoundMessage<G1>,
+                   m2: &BFDEVerifierFirstRoundMessage<G1>,
+                   m3: &BFDESignerSecondRoundMessage<G1>,
+                   message: &[Vec<u8>],
+                   k: G1::ScalarField,
+    ) -> Result<Vec<Signature<G1>>, FseError>
+    where
+        <G1 as CurveConfig>::BaseField: PrimeField,
+    {
+        let (alpha, beta, masked) = (&secret_randomness.alpha, &secret_randomness.beta, &m3.alpha);
+        if self.lambda == 0 || !self.well_formed(m1) || m2.b.len() != self.lambda || message.len() != self.n
+            || masked.len() != self.n || alpha.len() != self.n || beta.len() != self.n {
+            return Err(FseError::LengthMismatch);
+        }
+        let r_g = m1.answered(&m2.b);
+
+        if self.g.mul(k) != m3.com_k {
+            return Err(FseError::InvalidKey);
+        }
+
+        let signatures: Vec<Signature<G1>> = (0..self.n).into_par_iter().map(|i| {
+            Signature {
+                r_g: r_g[i].add(self.g.mul(alpha[i])).add(self.pk.pk.mul(beta[i])),
+                s: G1::ScalarField::from(2u8) * masked[i] - k + alpha[i],
+            }
+        }).collect();
+
+        // batch check first, only look for the culprits if it fails
+        let verifier = Verifier::new(self.pk.clone());
+        if !verifier.batch_verify(message, &signatures) {
+            let bad: Vec<usize> = (0..self.n).into_par_iter()
+                .filter(|i| !verifier.verify(&message[*i], &signatures[*i]))
+                .collect();
+            return Err(FseError::InvalidSignatures(bad));
+        }
+
+        Ok(signatures)
+    }
+}
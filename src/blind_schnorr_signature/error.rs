@@ -0,0 +1,20 @@
+use std::fmt;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BlindError {
+    /// The signer's answer does not verify against its commitment and the challenge
+    InvalidResponse,
+    /// The unblinded signature does not verify on the message
+    InvalidSignature,
+}
+
+impl fmt::Display for BlindError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BlindError::InvalidResponse => write!(f, "signer response does not verify"),
+            BlindError::InvalidSignature => write!(f, "unblinded signature does not verify"),
+        }
+    }
+}
+
+impl std::error::Error for BlindError {}
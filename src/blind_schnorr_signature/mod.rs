@@ -1,3 +1,5 @@
+pub mod error;
 pub mod signer;
 pub mod verifier;
+pub mod session;
 mod test;
\ No newline at end of file
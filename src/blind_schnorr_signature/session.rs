@@ -0,0 +1,85 @@
+use ark_ec::CurveConfig;
+use ark_ec::short_weierstrass::SWCurveConfig;
+use ark_ff::PrimeField;
+use rand::Rng;
+
+use crate::blind_schnorr_signature::error::BlindError;
+use crate::blind_schnorr_signature::signer::{BSSigner, BSSignerFirstRoundMessage, BSSignerSecondRoundMessage, BSSignerSecretRandomness};
+use crate::blind_schnorr_signature::verifier::{BSVerifier, BSVerifierFirstRoundMessage, BSVerifierSecretRandomness};
+use crate::schnorr_signature::signature::Signature;
+
+/// Signer session that has sent g^r and waits for the challenge. Answering consumes the
+/// session, so the nonce can never answer two challenges
+pub struct BSSignerAwaitingChallenge<'a, G1>
+where
+    G1: SWCurveConfig + Clone,
+    G1::ScalarField: PrimeField,
+{
+    signer: &'a BSSigner<G1>,
+    secret_randomness: BSSignerSecretRandomness<G1>,
+}
+
+/// Verifier session that has sent the blinded challenge and waits for the signer's answer
+pub struct BSVerifierAwaitingResponse<'a, G1>
+where
+    G1: SWCurveConfig + Clone,
+    G1::ScalarField: PrimeField,
+{
+    verifier: &'a BSVerifier<G1>,
+    secret_randomness: BSVerifierSecretRandomness<G1>,
+    m1: BSSignerFirstRoundMessage<G1>,
+    m2: BSVerifierFirstRoundMessage<G1>,
+}
+
+impl<G1> BSSigner<G1>
+where
+    G1: SWCurveConfig + Clone,
+    G1::ScalarField: PrimeField,
+{
+    pub fn open_session<R: Rng>(&self, rng: &mut R) -> (BSSignerAwaitingChallenge<'_, G1>, BSSignerFirstRoundMessage<G1>) {
+        let (secret_randomness, m1) = self.first_round(rng);
+        (BSSignerAwaitingChallenge { signer: self, secret_randomness }, m1)
+    }
+}
+
+impl<G1> BSSignerAwaitingChallenge<'_, G1>
+where
+    G1: SWCurveConfig + Clone,
+    G1::ScalarField: PrimeField,
+{
+    pub fn respond(self, m2: &BSVerifierFirstRoundMessage<G1>) -> BSSignerSecondRoundMessage<G1> {
+        self.signer.second_round(self.secret_randomness, m2)
+    }
+}
+
+impl<G1> BSVerifier<G1>
+where
+    G1: SWCurveConfig + Clone,
+    G1::ScalarField: PrimeField,
+{
+    pub fn open_session<R: Rng>(&self,
+                                m1: BSSignerFirstRoundMessage<G1>,
+                                message: Vec<u8>,
+                                rng: &mut R,
+    ) -> (BSVerifierAwaitingResponse<'_, G1>, BSVerifierFirstRoundMessage<G1>)
+    where
+        <G1 as CurveConfig>::BaseField: PrimeField,
+    {
+        let (secret_randomness, m2) = self.first_round(&m1, message, rng);
+        let c = m2.c;
+        (BSVerifierAwaitingResponse { verifier: self, secret_randomness, m1, m2 }, BSVerifierFirstRoundMessage { c })
+    }
+}
+
+impl<G1> BSVerifierAwaitingResponse<'_, G1>
+where
+    G1: SWCurveConfig + Clone,
+    G1::ScalarField: PrimeField,
+{
+    pub fn finish(self, m3: &BSSignerSecondRoundMessage<G1>) -> Result<Signature<G1>, BlindError>
+    where
+        <G1 as CurveConfig>::BaseField: PrimeField,
+    {
+        self.verifier.second_round(&self.secret_randomness, &self.m1, &self.m2, m3)
+    }
+}
@@ -8,6 +8,7 @@ use rand::Rng;
 use crate::blind_schnorr_signature::verifier::BSVerifierFirstRoundMessage;
 use crate::schnorr_signature::key::SecretKey;
 use crate::schnorr_signature::signer::Signer;
+use crate::schnorr_signature::util::erase_scalar;
 
 pub struct BSSigner<G1>
 where
@@ -18,12 +19,43 @@ where
     pub g: Projective<G1>,
 }
 
+/// Nonce of one session, consumed by the second round so it cannot answer twice:
+///
+/// ```compile_fail,E0382
+/// use ark_bn254::g1::Config;
+/// use fde::blind_schnorr_signature::signer::BSSigner;
+/// use fde::blind_schnorr_signature::verifier::BSVerifier;
+/// use fde::schnorr_signature::key::{generate_key_pair, PublicKey, SecretKey};
+/// use fde::schnorr_signature::signer::Signer;
+/// use fde::schnorr_signature::verifier::Verifier;
+/// use rand::thread_rng;
+///
+/// let (sk, pk): (SecretKey<Config>, PublicKey<Config>) = generate_key_pair(&mut thread_rng());
+/// let bs_signer = BSSigner::new(&Signer::new(sk));
+/// let bs_verifier = BSVerifier::new(&Verifier::new(pk));
+///
+/// let (secret_randomness, m1) = bs_signer.first_round(&mut thread_rng());
+/// let (_, m2_a) = bs_verifier.first_round(&m1, vec![0u8], &mut thread_rng());
+/// let (_, m2_b) = bs_verifier.first_round(&m1, vec![1u8], &mut thread_rng());
+/// bs_signer.second_round(secret_randomness, &m2_a);
+/// bs_signer.second_round(secret_randomness, &m2_b);
+/// ```
 pub struct BSSignerSecretRandomness<G1>
 where
     G1: SWCurveConfig + Clone,
     G1::ScalarField: PrimeField,
 {
-    pub r: G1::ScalarField,
+    r: G1::ScalarField,
+}
+
+impl<G1> Drop for BSSignerSecretRandomness<G1>
+where
+    G1: SWCurveConfig + Clone,
+    G1::ScalarField: PrimeField,
+{
+    fn drop(&mut self) {
+        erase_scalar(&mut self.r);
+    }
 }
 
 pub struct BSSignerFirstRoundMessage<G1>
@@ -63,7 +95,7 @@ where
         (BSSignerSecretRandomness { r }, BSSignerFirstRoundMessage { r_g })
     }
 
-    pub fn second_round(&self, secret_randomness: &BSSignerSecretRandomness<G1>, m1: &BSVerifierFirstRoundMessage<G1>) -> BSSignerSecondRoundMessage<G1> {
+    pub fn second_round(&self, secret_randomness: BSSignerSecretRandomness<G1>, m1: &BSVerifierFirstRoundMessage<G1>) -> BSSignerSecondRoundMessage<G1> {
         BSSignerSecondRoundMessage { s: secret_randomness.r + self.sk.sk * m1.c }
     }
 }
@@ -1,12 +1,14 @@
 #[cfg(test)]
 mod tests {
     use rand::thread_rng;
-    use crate::blind_schnorr_signature::signer::BSSigner;
+    use crate::blind_schnorr_signature::error::BlindError;
+    use crate::blind_schnorr_signature::signer::{BSSigner, BSSignerSecondRoundMessage};
     use crate::blind_schnorr_signature::verifier::BSVerifier;
     use crate::schnorr_signature::key::{generate_key_pair, PublicKey, SecretKey};
     use crate::schnorr_signature::signer::{Signer};
     use crate::schnorr_signature::verifier::{Verifier};
     use ark_bn254::g1::Config;
+    use ark_bn254::Fr;
 
     #[test]
     fn test_schnorr_signature() {
@@ -24,11 +26,37 @@ mod tests {
         // interaction
         let (signer_secret_randomness, m1) = bs_signer.first_round(&mut thread_rng());
         let (verifier_secret_randomness, m2) = bs_verifier.first_round(&m1, message.to_vec(), &mut thread_rng());
-        let m3 = bs_signer.second_round(&signer_secret_randomness, &m2);
-        let signature = bs_verifier.second_round(&verifier_secret_randomness, &m1, &m2, &m3);
+        let m3 = bs_signer.second_round(signer_secret_randomness, &m2);
+        let signature = bs_verifier.second_round(&verifier_secret_randomness, &m1, &m2, &m3).unwrap();
 
         assert!(verifier.verify(&message.to_vec(), &signature));
+
+        // a malicious answer is an error rather than a crash
+        let tampered = BSSignerSecondRoundMessage { s: m3.s + Fr::from(1u8) };
+        assert_eq!(bs_verifier.second_round(&verifier_secret_randomness, &m1, &m2, &tampered).err(), Some(BlindError::InvalidResponse));
+
+        // so is an answer that unblinds to a signature on another message
+        let mut other = verifier_secret_randomness;
+        other.message = vec![0u8];
+        assert_eq!(bs_verifier.second_round(&other, &m1, &m2, &m3).err(), Some(BlindError::InvalidSignature));
     }
-}
 
+    #[test]
+    fn test_session() {
+        let message = [0u8, 1u8, 2u8, 3u8];
+        let (sk, pk): (SecretKey<Config>, PublicKey<Config>) = generate_key_pair(&mut thread_rng());
+        let signer = Signer::new(sk);
+        let verifier = Verifier::new(pk);
 
+        let bs_signer = BSSigner::new(&signer);
+        let bs_verifier = BSVerifier::new(&verifier);
+
+        // every round consumes the previous state
+        let (signer_session, m1) = bs_signer.open_session(&mut thread_rng());
+        let (verifier_session, m2) = bs_verifier.open_session(m1, message.to_vec(), &mut thread_rng());
+        let m3 = signer_session.respond(&m2);
+        let signature = verifier_session.finish(&m3).unwrap();
+
+        assert!(verifier.verify(&message.to_vec(), &signature));
+    }
+}
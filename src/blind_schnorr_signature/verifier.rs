@@ -6,6 +6,7 @@ use ark_ff::PrimeField;
 use ark_std::UniformRand;
 use rand::Rng;
 
+use crate::blind_schnorr_signature::error::BlindError;
 use crate::blind_schnorr_signature::signer::{BSSignerFirstRoundMessage, BSSignerSecondRoundMessage};
 use crate::hash::Hash256;
 use crate::schnorr_signature::key::PublicKey;
@@ -22,6 +23,7 @@ where
     pub g: Projective<G1>,
 }
 
+/// Blinding factors of one session and the message they blind
 pub struct BSVerifierSecretRandomness<G1>
 where
     G1: SWCurveConfig + Clone,
@@ -29,6 +31,7 @@ where
 {
     pub alpha: G1::ScalarField,
     pub beta: G1::ScalarField,
+    pub message: Vec<u8>,
 }
 
 pub struct BSVerifierFirstRoundMessage<G1>
@@ -72,22 +75,29 @@ where
 
         let c_prime: G1::ScalarField = {
             let mut bytes = group_element_into_bytes::<G1>(&r_g_prime);
-            bytes.extend(message);
+            bytes.extend_from_slice(&message);
             Hash256::hash_bytes(bytes.as_slice())
         };
 
         let c = c_prime + beta;
 
-        (BSVerifierSecretRandomness { alpha, beta }, BSVerifierFirstRoundMessage { c })
+        (BSVerifierSecretRandomness { alpha, beta, message }, BSVerifierFirstRoundMessage { c })
     }
 
+    /// Unblind the signer's answer into a signature on the message blinded in the first round,
+    /// which is checked with the plain verifier before it is returned
     pub fn second_round(&self,
                         secret_randomness: &BSVerifierSecretRandomness<G1>,
                         m1: &BSSignerFirstRoundMessage<G1>,
                         m2: &BSVerifierFirstRoundMessage<G1>,
                         m3: &BSSignerSecondRoundMessage<G1>,
-    ) -> Signature<G1> {
-        assert_eq!(self.g.mul(m3.s), m1.r_g.add(self.pk.pk.mul(m2.c)));
+    ) -> Result<Signature<G1>, BlindError>
+    where
+        <G1 as CurveConfig>::BaseField: PrimeField,
+    {
+        if self.g.mul(m3.s) != m1.r_g.add(self.pk.pk.mul(m2.c)) {
+            return Err(BlindError::InvalidResponse);
+        }
 
         let s_prime = m3.s + secret_randomness.alpha;
 
@@ -99,10 +109,16 @@ where
             temp
         };
 
-        Signature {
+        let signature = Signature {
             r_g: r_g_prime,
             s: s_prime,
+        };
+
+        let verifier = Verifier { pk: self.pk.clone(), g: self.g };
+        if !verifier.verify(&secret_randomness.message, &signature) {
+            return Err(BlindError::InvalidSignature);
         }
+
+        Ok(signature)
     }
 }
-
@@ -0,0 +1,3 @@
+pub mod signer;
+pub mod verifier;
+mod test;
\ No newline at end of file
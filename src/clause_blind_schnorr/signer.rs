@@ -0,0 +1,87 @@
+use std::ops::Mul;
+
+use ark_ec::short_weierstrass::{Projective, SWCurveConfig};
+use ark_ff::PrimeField;
+use ark_std::UniformRand;
+use rand::Rng;
+
+use crate::clause_blind_schnorr::verifier::CBSVerifierFirstRoundMessage;
+use crate::schnorr_signature::key::SecretKey;
+use crate::schnorr_signature::signer::Signer;
+
+/// Clause Blind Schnorr signer (Fuchsbauer, Plouviez, Seurin 2020): every session commits to
+/// two nonces and answers only one of the two blinded challenges, chosen at random, which
+/// defeats the ROS attack on concurrent sessions of plain blind Schnorr
+pub struct CBSSigner<G1>
+where
+    G1: SWCurveConfig + Clone,
+    G1::ScalarField: PrimeField,
+{
+    pub sk: SecretKey<G1>,
+    pub g: Projective<G1>,
+}
+
+/// Nonces of one session, consumed by the second round so they cannot answer twice
+pub struct CBSSignerSecretRandomness<G1>
+where
+    G1: SWCurveConfig + Clone,
+    G1::ScalarField: PrimeField,
+{
+    r0: G1::ScalarField,
+    r1: G1::ScalarField,
+}
+
+pub struct CBSSignerFirstRoundMessage<G1>
+where
+    G1: SWCurveConfig + Clone,
+    G1::ScalarField: PrimeField,
+{
+    pub r0_g: Projective<G1>,
+    pub r1_g: Projective<G1>,
+}
+
+pub struct CBSSignerSecondRoundMessage<G1>
+where
+    G1: SWCurveConfig + Clone,
+    G1::ScalarField: PrimeField,
+{
+    /// The clause the signer answered
+    pub b: bool,
+    pub s: G1::ScalarField,
+}
+
+impl<G1> CBSSigner<G1>
+where
+    G1: SWCurveConfig + Clone,
+    G1::ScalarField: PrimeField,
+{
+    pub fn new(signer: &Signer<G1>) -> Self {
+        CBSSigner {
+            sk: signer.get_secret_key(),
+            g: signer.get_generator(),
+        }
+    }
+
+    pub fn first_round<R: Rng>(&self, rng: &mut R) -> (CBSSignerSecretRandomness<G1>, CBSSignerFirstRoundMessage<G1>) {
+        let r0 = G1::ScalarField::rand(rng);
+        let r1 = G1::ScalarField::rand(rng);
+
+        // return the tuple of (r, g^r) for both clauses
+        (CBSSignerSecretRandomness { r0, r1 }, CBSSignerFirstRoundMessage { r0_g: self.g.mul(r0), r1_g: self.g.mul(r1) })
+    }
+
+    pub fn second_round<R: Rng>(&self,
+                                secret_randomness: CBSSignerSecretRandomness<G1>,
+                                m1: &CBSVerifierFirstRoundMessage<G1>,
+                                rng: &mut R,
+    ) -> CBSSignerSecondRoundMessage<G1> {
+        let b = bool::rand(rng);
+        let (r, c) = if b {
+            (secret_randomness.r1, m1.c1)
+        } else {
+            (secret_randomness.r0, m1.c0)
+        };
+
+        CBSSignerSecondRoundMessage { b, s: r + self.sk.sk * c }
+    }
+}
@@ -0,0 +1,97 @@
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+    use std::thread;
+
+    use ark_bn254::g1::Config;
+    use ark_bn254::Fr;
+    use rand::seq::SliceRandom;
+    use rand::thread_rng;
+
+    use crate::blind_schnorr_signature::error::BlindError;
+    use crate::clause_blind_schnorr::signer::{CBSSigner, CBSSignerFirstRoundMessage, CBSSignerSecretRandomness};
+    use crate::clause_blind_schnorr::verifier::{CBSVerifier, CBSVerifierFirstRoundMessage, CBSVerifierSecretRandomness};
+    use crate::schnorr_signature::key::{generate_key_pair, PublicKey, SecretKey};
+    use crate::schnorr_signature::signature::Signature;
+    use crate::schnorr_signature::signer::Signer;
+    use crate::schnorr_signature::verifier::Verifier;
+
+    struct Session {
+        message: Vec<u8>,
+        signer_secret_randomness: CBSSignerSecretRandomness<Config>,
+        m1: CBSSignerFirstRoundMessage<Config>,
+        verifier_secret_randomness: CBSVerifierSecretRandomness<Config>,
+        m2: CBSVerifierFirstRoundMessage<Config>,
+    }
+
+    /// Open `sessions` sessions before closing any, then close them in random order from
+    /// `threads` threads sharing one signer, returns the messages, signatures and answered clauses
+    fn run_concurrent_sessions(signer: &CBSSigner<Config>, verifier: &CBSVerifier<Config>, threads: usize, sessions: usize) -> Vec<(Vec<u8>, Signature<Config>, bool)> {
+        let mut open: Vec<Session> = (0..sessions).map(|i| {
+            let message = (i as u64).to_le_bytes().to_vec();
+            let (signer_secret_randomness, m1) = signer.first_round(&mut thread_rng());
+            let (verifier_secret_randomness, m2) = verifier.first_round(&m1, &message, &mut thread_rng());
+            Session { message, signer_secret_randomness, m1, verifier_secret_randomness, m2 }
+        }).collect();
+        open.shuffle(&mut thread_rng());
+
+        let queue = Mutex::new(open);
+        let done = Mutex::new(Vec::with_capacity(sessions));
+        thread::scope(|scope| {
+            for _ in 0..threads {
+                scope.spawn(|| loop {
+                    let Some(session) = queue.lock().unwrap().pop() else { break };
+                    let m3 = signer.second_round(session.signer_secret_randomness, &session.m2, &mut thread_rng());
+                    let signature = verifier.second_round(&session.verifier_secret_randomness, &session.m1, &session.m2, &m3).unwrap();
+                    done.lock().unwrap().push((session.message, signature, m3.b));
+                });
+            }
+        });
+
+        done.into_inner().unwrap()
+    }
+
+    #[test]
+    fn test_schnorr_signature() {
+        let message = [0u8, 1u8, 2u8, 3u8];
+        let (sk, pk): (SecretKey<Config>, PublicKey<Config>) = generate_key_pair(&mut thread_rng());
+
+        // non-blind signer/verifier
+        let signer = Signer::new(sk);
+        let verifier = Verifier::new(pk);
+
+        // blind signer/verifier
+        let cbs_signer = CBSSigner::new(&signer);
+        let cbs_verifier = CBSVerifier::new(&verifier);
+
+        // interaction
+        let (signer_secret_randomness, m1) = cbs_signer.first_round(&mut thread_rng());
+        let (verifier_secret_randomness, m2) = cbs_verifier.first_round(&m1, &message, &mut thread_rng());
+        let m3 = cbs_signer.second_round(signer_secret_randomness, &m2, &mut thread_rng());
+        let signature = cbs_verifier.second_round(&verifier_secret_randomness, &m1, &m2, &m3).unwrap();
+
+        assert!(verifier.verify(&message.to_vec(), &signature));
+
+        // a tampered answer is rejected
+        let mut m3 = m3;
+        m3.s += Fr::from(1u8);
+        assert_eq!(cbs_verifier.second_round(&verifier_secret_randomness, &m1, &m2, &m3).err(), Some(BlindError::InvalidResponse));
+    }
+
+    #[test]
+    fn test_concurrent_sessions() {
+        let (sk, pk): (SecretKey<Config>, PublicKey<Config>) = generate_key_pair(&mut thread_rng());
+        let signer = Signer::new(sk);
+        let verifier = Verifier::new(pk);
+
+        let results = run_concurrent_sessions(&CBSSigner::new(&signer), &CBSVerifier::new(&verifier), 8, 128);
+        assert_eq!(results.len(), 128);
+
+        // every session yields a plain Schnorr signature
+        let (message, signatures): (Vec<Vec<u8>>, Vec<Signature<Config>>) = results.iter().map(|(m, sig, _)| (m.clone(), sig.clone())).unzip();
+        assert!(verifier.batch_verify(&message, &signatures));
+
+        // the signer, not the user, picks the clause, and both get picked
+        assert!(results.iter().any(|(_, _, b)| *b) && results.iter().any(|(_, _, b)| !*b));
+    }
+}
@@ -0,0 +1,124 @@
+use std::ops::{Add, Mul};
+
+use ark_ec::CurveConfig;
+use ark_ec::short_weierstrass::{Projective, SWCurveConfig};
+use ark_ff::PrimeField;
+use ark_std::UniformRand;
+use rand::Rng;
+
+use crate::blind_schnorr_signature::error::BlindError;
+use crate::clause_blind_schnorr::signer::{CBSSignerFirstRoundMessage, CBSSignerSecondRoundMessage};
+use crate::hash::Hash256;
+use crate::schnorr_signature::key::PublicKey;
+use crate::schnorr_signature::signature::Signature;
+use crate::schnorr_signature::util::group_element_into_bytes;
+use crate::schnorr_signature::verifier::Verifier;
+
+pub struct CBSVerifier<G1>
+where
+    G1: SWCurveConfig + Clone,
+    G1::ScalarField: PrimeField,
+{
+    pub pk: PublicKey<G1>,
+    pub g: Projective<G1>,
+}
+
+/// Blinding factors of both clauses and the message they blind
+pub struct CBSVerifierSecretRandomness<G1>
+where
+    G1: SWCurveConfig + Clone,
+    G1::ScalarField: PrimeField,
+{
+    pub alpha_0: G1::ScalarField,
+    pub beta_0: G1::ScalarField,
+    pub alpha_1: G1::ScalarField,
+    pub beta_1: G1::ScalarField,
+    pub message: Vec<u8>,
+}
+
+pub struct CBSVerifierFirstRoundMessage<G1>
+where
+    G1: SWCurveConfig + Clone,
+    G1::ScalarField: PrimeField,
+{
+    pub c0: G1::ScalarField,
+    pub c1: G1::ScalarField,
+}
+
+impl<G1> CBSVerifier<G1>
+where
+    G1: SWCurveConfig + Clone,
+    G1::ScalarField: PrimeField,
+{
+    pub fn new(verifier: &Verifier<G1>) -> Self {
+        CBSVerifier {
+            pk: verifier.pk.clone(),
+            g: verifier.get_generator(),
+        }
+    }
+
+    /// R' = R * g^{alpha} * pk^{beta}
+    fn blind(&self, r_g: &Projective<G1>, alpha: G1::ScalarField, beta: G1::ScalarField) -> Projective<G1> {
+        self.g.mul(alpha).add(self.pk.pk.mul(beta)).add(r_g)
+    }
+
+    pub fn first_round<R: Rng>(&self,
+                               m1: &CBSSignerFirstRoundMessage<G1>,
+                               message: &[u8],
+                               rng: &mut R,
+    ) -> (CBSVerifierSecretRandomness<G1>, CBSVerifierFirstRoundMessage<G1>)
+    where
+        <G1 as CurveConfig>::BaseField: PrimeField,
+    {
+        let alpha_0 = G1::ScalarField::rand(rng);
+        let beta_0 = G1::ScalarField::rand(rng);
+        let alpha_1 = G1::ScalarField::rand(rng);
+        let beta_1 = G1::ScalarField::rand(rng);
+
+        // the same message is blinded under both clauses
+        let challenge = |r_g_prime: Projective<G1>, beta: G1::ScalarField| {
+            let mut bytes = group_element_into_bytes::<G1>(&r_g_prime);
+            bytes.extend_from_slice(message);
+            Hash256::hash_bytes::<G1::ScalarField>(bytes.as_slice()) + beta
+        };
+        let c0 = challenge(self.blind(&m1.r0_g, alpha_0, beta_0), beta_0);
+        let c1 = challenge(self.blind(&m1.r1_g, alpha_1, beta_1), beta_1);
+
+        let message = message.to_vec();
+        (CBSVerifierSecretRandomness { alpha_0, beta_0, alpha_1, beta_1, message }, CBSVerifierFirstRoundMessage { c0, c1 })
+    }
+
+    /// Unblind the answered clause into a signature on the message blinded in the first round,
+    /// which is checked with the plain verifier before it is returned
+    pub fn second_round(&self,
+                        secret_randomness: &CBSVerifierSecretRandomness<G1>,
+                        m1: &CBSSignerFirstRoundMessage<G1>,
+                        m2: &CBSVerifierFirstRoundMessage<G1>,
+                        m3: &CBSSignerSecondRoundMessage<G1>,
+    ) -> Result<Signature<G1>, BlindError>
+    where
+        <G1 as CurveConfig>::BaseField: PrimeField,
+    {
+        let (r_g, c, alpha, beta) = if m3.b {
+            (&m1.r1_g, m2.c1, secret_randomness.alpha_1, secret_randomness.beta_1)
+        } else {
+            (&m1.r0_g, m2.c0, secret_randomness.alpha_0, secret_randomness.beta_0)
+        };
+
+        if self.g.mul(m3.s) != r_g.add(self.pk.pk.mul(c)) {
+            return Err(BlindError::InvalidResponse);
+        }
+
+        let signature = Signature {
+            r_g: self.blind(r_g, alpha, beta),
+            s: m3.s + alpha,
+        };
+
+        let verifier = Verifier { pk: self.pk.clone(), g: self.g };
+        if !verifier.verify(&secret_randomness.message, &signature) {
+            return Err(BlindError::InvalidSignature);
+        }
+
+        Ok(signature)
+    }
+}
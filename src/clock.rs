@@ -0,0 +1,71 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Source of the current time, in seconds
+pub trait Clock {
+    fn now(&self) -> u64;
+}
+
+/// Wall clock time since the unix epoch
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> u64 {
+        SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+    }
+}
+
+/// Manually driven clock for deterministic tests
+#[derive(Debug, Default)]
+pub struct MockClock {
+    now: AtomicU64,
+}
+
+impl MockClock {
+    pub fn new(now: u64) -> Self {
+        MockClock { now: AtomicU64::new(now) }
+    }
+
+    pub fn set(&self, now: u64) {
+        self.now.store(now, Ordering::SeqCst);
+    }
+
+    pub fn advance(&self, seconds: u64) {
+        self.now.fetch_add(seconds, Ordering::SeqCst);
+    }
+}
+
+impl Clock for MockClock {
+    fn now(&self) -> u64 {
+        self.now.load(Ordering::SeqCst)
+    }
+}
+
+impl<C: Clock + ?Sized> Clock for &C {
+    fn now(&self) -> u64 {
+        (**self).now()
+    }
+}
+
+impl<C: Clock + ?Sized> Clock for Arc<C> {
+    fn now(&self) -> u64 {
+        (**self).now()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::clock::{Clock, MockClock};
+
+    #[test]
+    fn test_mock_clock() {
+        let clock = MockClock::new(100);
+        assert_eq!(clock.now(), 100);
+        clock.advance(5);
+        assert_eq!(clock.now(), 105);
+        clock.set(7);
+        assert_eq!(clock.now(), 7);
+    }
+}
@@ -0,0 +1,132 @@
+use std::marker::PhantomData;
+use std::ops::Mul;
+
+use ark_ec::{CurveConfig, Group};
+use ark_ec::short_weierstrass::{Projective, SWCurveConfig};
+use ark_ff::PrimeField;
+use ark_std::UniformRand;
+use rand::Rng;
+use rayon::iter::{IntoParallelIterator, IntoParallelRefIterator};
+use rayon::iter::ParallelIterator;
+
+use crate::hash::Hash256;
+use crate::schnorr_signature::adaptor::{adapt, extract};
+use crate::schnorr_signature::key::{generate_key_pair, PublicKey, SecretKey};
+use crate::schnorr_signature::signature::{PreSignature, Signature};
+use crate::schnorr_signature::util::group_element_into_bytes;
+
+/// Batch adaptor signatures: n pre-signatures locked to a single statement T = g^t,
+/// publishing any one completed signature reveals t and with it all n signatures
+pub struct AdaptorFSE<G1>
+where
+    G1: SWCurveConfig + Clone,
+    G1::ScalarField: PrimeField,
+{
+    phantom: PhantomData<G1>,
+}
+
+impl<G1> AdaptorFSE<G1>
+where
+    G1: SWCurveConfig + Clone,
+    G1::ScalarField: PrimeField,
+{
+    pub fn gen_key<R: Rng>(rng: &mut R) -> (SecretKey<G1>, PublicKey<G1>) {
+        generate_key_pair(rng)
+    }
+
+    pub fn sign<R: Rng>(sk: &SecretKey<G1>, message: &[Vec<u8>], rng: &mut R) -> (Vec<PreSignature<G1>>, Projective<G1>, G1::ScalarField)
+    where
+        <G1 as CurveConfig>::BaseField: PrimeField,
+    {
+        let n = message.len();
+
+        let r: Vec<G1::ScalarField> = (0..n).map(|_| G1::ScalarField::rand(rng)).collect();
+
+        let t = G1::ScalarField::rand(rng);
+        let t_g = Projective::generator().mul(t);
+
+        // R'_i = g^r_i * T
+        let r_g: Vec<Projective<G1>> = r.par_iter().map(|r_i| Projective::generator().mul(*r_i) + t_g).collect();
+
+        let pre_signatures: Vec<PreSignature<G1>> = (0..n).into_par_iter().map(|i| {
+            let c_i: G1::ScalarField = {
+                let mut bytes = group_element_into_bytes::<G1>(&r_g[i]);
+                bytes.extend_from_slice(&message[i]);
+                Hash256::hash_bytes(bytes.as_slice())
+            };
+
+            PreSignature {
+                r_g: r_g[i],
+                s: r[i] + c_i * sk.sk,
+            }
+        }).collect();
+
+        (pre_signatures, t_g, t)
+    }
+
+    pub fn verify(pk: &PublicKey<G1>, message: &[Vec<u8>], pre_signatures: &[PreSignature<G1>], t_g: &Projective<G1>) -> bool
+    where
+        <G1 as CurveConfig>::BaseField: PrimeField,
+    {
+        if message.len() != pre_signatures.len() {
+            return false;
+        }
+
+        (0..message.len()).into_par_iter().map(|i| {
+            let c_i: G1::ScalarField = {
+                let mut bytes = group_element_into_bytes::<G1>(&pre_signatures[i].r_g);
+                bytes.extend_from_slice(&message[i]);
+                Hash256::hash_bytes(bytes.as_slice())
+            };
+
+            // g^s'_i == R'_i / T * pk^c_i
+            Projective::generator().mul(pre_signatures[i].s) == pre_signatures[i].r_g - t_g + pk.pk.mul(c_i)
+        }).reduce(|| true, |acc, x| acc && x)
+    }
+
+    pub fn recover(pre_signatures: &[PreSignature<G1>], t: G1::ScalarField) -> Vec<Signature<G1>> {
+        pre_signatures.iter().map(|pre_signature| adapt(pre_signature, t)).collect()
+    }
+
+    /// Extract t from any completed signature of the batch
+    pub fn extract(pre_signatures: &[PreSignature<G1>], index: usize, signature: &Signature<G1>) -> Option<G1::ScalarField> {
+        pre_signatures.get(index).and_then(|pre_signature| extract(pre_signature, signature))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_bn254::g1::Config;
+    use rand::thread_rng;
+
+    use crate::fse::adaptor::AdaptorFSE;
+    use crate::schnorr_signature::key::{PublicKey, SecretKey};
+    use crate::schnorr_signature::verifier::Verifier;
+
+    #[test]
+    fn test() {
+        let message = vec![
+            [0u8, 1u8, 2u8, 3u8].to_vec(),
+            [4u8, 5u8, 6u8, 7u8].to_vec(),
+        ];
+        let (sk, pk): (SecretKey<Config>, PublicKey<Config>) = AdaptorFSE::gen_key(&mut thread_rng());
+
+        let (pre_signatures, t_g, t) = AdaptorFSE::sign(&sk, &message, &mut thread_rng());
+        assert!(AdaptorFSE::verify(&pk, &message, &pre_signatures, &t_g));
+
+        let signatures = AdaptorFSE::recover(&pre_signatures, t);
+
+        let verifier = Verifier::new(pk.clone());
+        for (i, sig) in signatures.iter().enumerate() {
+            assert!(verifier.verify(&message[i], sig));
+        }
+
+        // one published signature is enough to learn t
+        assert_eq!(AdaptorFSE::extract(&pre_signatures, 1, &signatures[1]), Some(t));
+        assert_eq!(AdaptorFSE::extract(&pre_signatures, 0, &signatures[1]), None);
+
+        // pre-signatures under another statement are rejected
+        let (_, other_t_g, _) = AdaptorFSE::<Config>::sign(&sk, &message, &mut thread_rng());
+        assert!(!AdaptorFSE::verify(&pk, &message, &pre_signatures, &other_t_g));
+    }
+}
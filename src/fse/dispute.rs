@@ -0,0 +1,244 @@
+use std::marker::PhantomData;
+use std::ops::Mul;
+
+use ark_ec::{CurveConfig, Group};
+use ark_ec::short_weierstrass::{Projective, SWCurveConfig};
+use ark_ff::PrimeField;
+
+use crate::clock::Clock;
+use crate::fse::offer::FseOffer;
+use crate::fse::signed_offer::{verify_offer_signature, SignedOffer};
+use crate::ledger::chain::{Ledger, LedgerError};
+use crate::ledger::contract::ContractId;
+use crate::merkle::{Digest, MerkleProof};
+use crate::schnorr_signature::key::PublicKey;
+use crate::schnorr_signature::signature::Signature;
+use crate::schnorr_signature::verifier::Verifier;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Party {
+    Seller,
+    Buyer,
+}
+
+/// Self-contained evidence that the revealed k does not unlock entry `proof.index`
+/// of an offer the seller signed
+pub struct FraudProof<G1>
+where
+    G1: SWCurveConfig + Clone,
+    G1::ScalarField: PrimeField,
+{
+    /// The signed offer, reduced to its commitment
+    pub root: Digest,
+    pub com_k: Projective<G1>,
+    pub n: usize,
+    pub buyer: PublicKey<G1>,
+    pub expiry: u64,
+    pub nonce: [u8; 32],
+    pub offer_signature: Signature<G1>,
+    /// The disputed entry and its inclusion proof
+    pub message: Vec<u8>,
+    pub r_g: Projective<G1>,
+    pub alpha: G1::ScalarField,
+    pub proof: MerkleProof,
+    /// The key the seller revealed
+    pub k: G1::ScalarField,
+}
+
+impl<G1> FraudProof<G1>
+where
+    G1: SWCurveConfig + Clone,
+    G1::ScalarField: PrimeField,
+    <G1 as CurveConfig>::BaseField: PrimeField,
+{
+    /// Buyer: build the proof for a bad index, e.g. one reported by FSE::recover
+    pub fn new(signed: &SignedOffer<G1>, message: &[Vec<u8>], index: usize, k: G1::ScalarField) -> Option<Self> {
+        let offer = &signed.offer;
+        let proof = offer.prove(message, index)?;
+        Some(FraudProof {
+            root: offer.root,
+            com_k: offer.com_k,
+            n: offer.len(),
+            buyer: signed.buyer.clone(),
+            expiry: signed.expiry,
+            nonce: signed.nonce,
+            offer_signature: signed.signature.clone(),
+            message: message[index].clone(),
+            r_g: offer.r_g[index],
+            alpha: offer.alpha[index],
+            proof,
+            k,
+        })
+    }
+
+    pub fn index(&self) -> usize {
+        self.proof.index
+    }
+
+    /// True iff the seller signed the offer, the entry is in it, k opens com_k
+    /// and yet 2 * alpha - k is not a valid signature on the message
+    pub fn verify(&self, seller_pk: &PublicKey<G1>) -> bool {
+        let digest = FseOffer::digest_of(&self.root, &self.com_k, self.n);
+        if !verify_offer_signature(seller_pk, &digest, &self.buyer, self.expiry, &self.nonce, &self.offer_signature) {
+            return false;
+        }
+        if self.index() >= self.n || !FseOffer::verify_inclusion(&self.root, &self.message, &self.r_g, &self.alpha, &self.proof) {
+            return false;
+        }
+        if Projective::generator().mul(self.k) != self.com_k {
+            return false;
+        }
+
+        let signature = Signature {
+            r_g: self.r_g,
+            s: G1::ScalarField::from(2u128) * self.alpha - self.k,
+        };
+        !Verifier::new(seller_pk.clone()).verify(&self.message, &signature)
+    }
+}
+
+/// Arbiter's finding for the buyer, who is owed the key behind com_k.
+/// Only the arbiter issues one, the escrow agents release their shares on it
+pub struct Ruling<G1>
+where
+    G1: SWCurveConfig + Clone,
+    G1::ScalarField: PrimeField,
+{
+    com_k: Projective<G1>,
+}
+
+impl<G1> Ruling<G1>
+where
+    G1: SWCurveConfig + Clone,
+    G1::ScalarField: PrimeField,
+{
+    pub fn com_k(&self) -> &Projective<G1> {
+        &self.com_k
+    }
+}
+
+pub struct Arbiter<G1>
+where
+    G1: SWCurveConfig + Clone,
+    G1::ScalarField: PrimeField,
+{
+    phantom: PhantomData<G1>,
+}
+
+impl<G1> Arbiter<G1>
+where
+    G1: SWCurveConfig + Clone,
+    G1::ScalarField: PrimeField,
+    <G1 as CurveConfig>::BaseField: PrimeField,
+{
+    /// Name the cheating party: the seller if the fraud proof holds, otherwise the buyer who filed it
+    pub fn judge(seller_pk: &PublicKey<G1>, proof: &FraudProof<G1>) -> Party {
+        if proof.verify(seller_pk) {
+            Party::Seller
+        } else {
+            Party::Buyer
+        }
+    }
+
+    /// Rule for the buyer on a fraud proof the seller is found guilty of
+    pub fn rule_on_fraud(seller_pk: &PublicKey<G1>, proof: &FraudProof<G1>) -> Option<Ruling<G1>> {
+        (Self::judge(seller_pk, proof) == Party::Seller).then_some(Ruling { com_k: proof.com_k })
+    }
+
+    /// Rule for the buyer on a payment the seller let lapse without revealing k: the locked
+    /// funds go to the seller, and k to the buyer through the escrow agents. Fails unless the
+    /// contract is still locked and its deadline has passed
+    pub fn rule_on_non_reveal<C: Clock>(ledger: &mut Ledger<G1, C>, id: ContractId) -> Result<Ruling<G1>, LedgerError> {
+        ledger.settle_by_escrow(id)?;
+        let contract = ledger.contract(id).ok_or(LedgerError::UnknownContract(id))?;
+        Ok(Ruling { com_k: contract.com_k })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_bn254::g1::Config;
+    use ark_bn254::Fr;
+    use ark_ec::short_weierstrass::Projective;
+    use ark_ff::Zero;
+    use rand::thread_rng;
+
+    use crate::fse::dispute::{Arbiter, FraudProof, Party};
+    use crate::fse::error::FseError;
+    use crate::fse::offer::FseOffer;
+    use crate::fse::protocol::FSE;
+    use crate::fse::signed_offer::SignedOffer;
+    use crate::schnorr_signature::key::{PublicKey, SecretKey};
+
+    fn message() -> Vec<Vec<u8>> {
+        (0u8..4u8).map(|i| [i, 1u8, 2u8, 3u8].to_vec()).collect()
+    }
+
+    #[test]
+    fn test_cheating_seller() {
+        let message = message();
+        let (sk, pk): (SecretKey<Config>, PublicKey<Config>) = FSE::gen_key(&mut thread_rng());
+        let (_, buyer_pk): (SecretKey<Config>, PublicKey<Config>) = FSE::gen_key(&mut thread_rng());
+
+        // the seller signs an offer with a corrupted entry
+        let (mut offer, k) = FseOffer::sign(&sk, &message, &mut thread_rng());
+        offer.alpha[2] += Fr::from(1u8);
+        let offer = FseOffer::new(&message, offer.alpha, offer.r_g, offer.com_k);
+        let signed = SignedOffer::sign(&sk, offer.clone(), &buyer_pk, 160, &mut thread_rng());
+
+        let bad = match FSE::recover(&pk, &message, &offer.alpha, &offer.r_g, &offer.com_k, k) {
+            Err(FseError::InvalidSignatures(bad)) => bad,
+            _ => panic!("expected invalid signatures"),
+        };
+        assert_eq!(bad, vec![2]);
+
+        let proof = FraudProof::new(&signed, &message, bad[0], k).unwrap();
+        assert!(proof.verify(&pk));
+        assert_eq!(Arbiter::judge(&pk, &proof), Party::Seller);
+    }
+
+    #[test]
+    fn test_false_claim() {
+        let message = message();
+        let (sk, pk): (SecretKey<Config>, PublicKey<Config>) = FSE::gen_key(&mut thread_rng());
+        let (_, buyer_pk): (SecretKey<Config>, PublicKey<Config>) = FSE::gen_key(&mut thread_rng());
+
+        let (offer, k) = FseOffer::sign(&sk, &message, &mut thread_rng());
+        let signed = SignedOffer::sign(&sk, offer, &buyer_pk, 160, &mut thread_rng());
+
+        // the entry unlocks fine, there is no fraud to prove
+        let proof = FraudProof::new(&signed, &message, 1, k).unwrap();
+        assert!(!proof.verify(&pk));
+        assert_eq!(Arbiter::judge(&pk, &proof), Party::Buyer);
+
+        // a made-up key does not open com_k
+        let proof = FraudProof::new(&signed, &message, 1, k + Fr::from(1u8)).unwrap();
+        assert_eq!(Arbiter::judge(&pk, &proof), Party::Buyer);
+
+        // a tampered entry is not in the signed offer
+        let mut proof = FraudProof::new(&signed, &message, 1, k).unwrap();
+        proof.alpha += Fr::from(1u8);
+        assert_eq!(Arbiter::judge(&pk, &proof), Party::Buyer);
+
+        // an offer the seller never signed
+        let (other, _) = FseOffer::sign(&sk, &message, &mut thread_rng());
+        let unsigned = SignedOffer { offer: other, ..signed.clone() };
+        let proof = FraudProof::new(&unsigned, &message, 1, k).unwrap();
+        assert_eq!(Arbiter::judge(&pk, &proof), Party::Buyer);
+
+        // terms other than the signed ones
+        let mut proof = FraudProof::new(&signed, &message, 1, k).unwrap();
+        proof.expiry += 1;
+        assert_eq!(Arbiter::judge(&pk, &proof), Party::Buyer);
+        let mut proof = FraudProof::new(&signed, &message, 1, k).unwrap();
+        proof.nonce[0] ^= 1;
+        assert_eq!(Arbiter::judge(&pk, &proof), Party::Buyer);
+
+        // nor can points at infinity in the claim crash the arbiter
+        let mut proof = FraudProof::new(&signed, &message, 1, k).unwrap();
+        proof.buyer = PublicKey { pk: Projective::zero() };
+        proof.com_k = Projective::zero();
+        proof.r_g = Projective::zero();
+        assert_eq!(Arbiter::judge(&pk, &proof), Party::Buyer);
+    }
+}
@@ -0,0 +1,29 @@
+use std::fmt;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FseError {
+    /// The offer's vectors do not all have one entry per message
+    LengthMismatch,
+    /// The revealed k does not open com_k
+    InvalidKey,
+    /// The unmasked signatures at these indices do not verify, evidence against the seller
+    InvalidSignatures(Vec<usize>),
+    /// The period is not covered by the key chain
+    PeriodOutOfRange(usize),
+    /// The signer's answer does not check against its commitments
+    InvalidResponse,
+}
+
+impl fmt::Display for FseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FseError::LengthMismatch => write!(f, "offer length does not match the messages"),
+            FseError::InvalidKey => write!(f, "revealed key does not open com_k"),
+            FseError::InvalidSignatures(indices) => write!(f, "invalid signatures at indices {:?}", indices),
+            FseError::PeriodOutOfRange(t) => write!(f, "period {} is outside the key chain", t),
+            FseError::InvalidResponse => write!(f, "signer's answer does not check out"),
+        }
+    }
+}
+
+impl std::error::Error for FseError {}
\ No newline at end of file
@@ -0,0 +1,258 @@
+use std::collections::HashSet;
+use std::fmt;
+use std::marker::PhantomData;
+use std::ops::Mul;
+
+use ark_ec::{CurveGroup, Group, VariableBaseMSM};
+use ark_ec::short_weierstrass::{Projective, SWCurveConfig};
+use ark_ff::{Field, PrimeField, Zero};
+use ark_std::UniformRand;
+use rand::Rng;
+
+use crate::fse::dispute::Ruling;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum EscrowError {
+    /// The threshold is 0 or above the number of agents
+    InvalidThreshold { t: usize, m: usize },
+}
+
+impl fmt::Display for EscrowError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EscrowError::InvalidThreshold { t, m } => write!(f, "threshold {} is not between 1 and {} agents", t, m),
+        }
+    }
+}
+
+impl std::error::Error for EscrowError {}
+
+/// Share of k held by escrow agent `index`, the evaluation f(index) of the dealing polynomial
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Share<F: PrimeField> {
+    pub index: u64,
+    pub value: F,
+}
+
+/// Feldman secret sharing of the FSE key k among m escrow agents, any t of which can rebuild it.
+/// The commitments are g^{a_j} for the coefficients of f, so the constant one is com_k itself
+pub struct KeyEscrow<G1>
+where
+    G1: SWCurveConfig + Clone,
+    G1::ScalarField: PrimeField,
+{
+    phantom: PhantomData<G1>,
+}
+
+impl<G1> KeyEscrow<G1>
+where
+    G1: SWCurveConfig + Clone,
+    G1::ScalarField: PrimeField,
+{
+    /// Seller: share k with threshold t among agents 1..=m, returns the commitments and the shares.
+    /// Fails with InvalidThreshold unless 0 < t <= m
+    #[allow(clippy::type_complexity)]
+    pub fn deal<R: Rng>(k: G1::ScalarField, t: usize, m: usize, rng: &mut R) -> Result<(Vec<Projective<G1>>, Vec<Share<G1::ScalarField>>), EscrowError> {
+        if t == 0 || t > m {
+            return Err(EscrowError::InvalidThreshold { t, m });
+        }
+
+        let mut coefficients = vec![k];
+        coefficients.extend((1..t).map(|_| G1::ScalarField::rand(rng)));
+
+        let commitments = coefficients.iter().map(|a_j| Projective::generator().mul(*a_j)).collect();
+        let shares = (1..=m as u64).map(|index| {
+            let x = G1::ScalarField::from(index);
+            let value = coefficients.iter().rev().fold(G1::ScalarField::zero(), |acc, a_j| acc * x + a_j);
+            Share { index, value }
+        }).collect();
+
+        Ok((commitments, shares))
+    }
+
+    /// Buyer: the sharing has threshold t and its secret is the dlog of com_k
+    pub fn verify_commitments(com_k: &Projective<G1>, commitments: &[Projective<G1>], t: usize) -> bool {
+        commitments.len() == t && commitments.first() == Some(com_k)
+    }
+
+    /// Agent: g^{f(i)} equals the product of the commitments raised to i^j
+    pub fn verify_share(commitments: &[Projective<G1>], share: &Share<G1::ScalarField>) -> bool {
+        if share.index == 0 || commitments.is_empty() {
+            return false;
+        }
+
+        let x = G1::ScalarField::from(share.index);
+        let powers: Vec<G1::ScalarField> = std::iter::successors(Some(G1::ScalarField::ONE), |p| Some(*p * x))
+            .take(commitments.len())
+            .collect();
+        Projective::msm_unchecked(&Projective::normalize_batch(commitments), &powers) == Projective::generator().mul(share.value)
+    }
+
+    /// Lagrange interpolation of f(0) from the shares, None unless the result opens com_k,
+    /// which also catches fewer than t shares or a wrong one
+    pub fn reconstruct(com_k: &Projective<G1>, shares: &[Share<G1::ScalarField>]) -> Option<G1::ScalarField> {
+        let mut seen = HashSet::with_capacity(shares.len());
+        if shares.is_empty() || !shares.iter().all(|s| s.index != 0 && seen.insert(s.index)) {
+            return None;
+        }
+
+        let x: Vec<G1::ScalarField> = shares.iter().map(|s| G1::ScalarField::from(s.index)).collect();
+        let k = shares.iter().enumerate().map(|(i, share)| {
+            let (num, den) = x.iter().enumerate().filter(|(j, _)| *j != i)
+                .fold((G1::ScalarField::ONE, G1::ScalarField::ONE), |(num, den), (_, x_j)| (num * x_j, den * (*x_j - x[i])));
+            share.value * num * den.inverse().unwrap()
+        }).sum();
+
+        (Projective::generator().mul(k) == *com_k).then_some(k)
+    }
+}
+
+/// Escrow agent holding one verified share, released to the buyer only on the arbiter's ruling
+pub struct EscrowAgent<G1>
+where
+    G1: SWCurveConfig + Clone,
+    G1::ScalarField: PrimeField,
+{
+    pub commitments: Vec<Projective<G1>>,
+    share: Share<G1::ScalarField>,
+}
+
+impl<G1> EscrowAgent<G1>
+where
+    G1: SWCurveConfig + Clone,
+    G1::ScalarField: PrimeField,
+{
+    /// None if the share does not match the dealer's commitments, in which case the agent complains
+    pub fn new(commitments: Vec<Projective<G1>>, share: Share<G1::ScalarField>) -> Option<Self> {
+        KeyEscrow::verify_share(&commitments, &share).then_some(EscrowAgent { commitments, share })
+    }
+
+    pub fn index(&self) -> u64 {
+        self.share.index
+    }
+
+    /// Hand out the share only on a ruling for the buyer of the escrowed key, whether the
+    /// seller cheated on the offer or never revealed k
+    pub fn release(&self, ruling: &Ruling<G1>) -> Option<Share<G1::ScalarField>> {
+        (self.commitments.first() == Some(ruling.com_k())).then(|| self.share.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_bn254::g1::Config;
+    use ark_bn254::Fr;
+    use ark_ec::short_weierstrass::Projective;
+    use rand::thread_rng;
+
+    use crate::clock::MockClock;
+    use crate::fse::dispute::{Arbiter, FraudProof};
+    use crate::fse::escrow::{EscrowAgent, EscrowError, KeyEscrow};
+    use crate::fse::offer::FseOffer;
+    use crate::fse::protocol::FSE;
+    use crate::fse::signed_offer::SignedOffer;
+    use crate::ledger::chain::{Ledger, LedgerError};
+    use crate::schnorr_signature::key::{PublicKey, SecretKey};
+
+    fn agents(k: Fr, com_k: &Projective<Config>) -> Vec<EscrowAgent<Config>> {
+        let (commitments, shares) = KeyEscrow::<Config>::deal(k, 3, 5, &mut thread_rng()).unwrap();
+        assert!(KeyEscrow::verify_commitments(com_k, &commitments, 3));
+        shares.into_iter()
+            .map(|share| EscrowAgent::new(commitments.clone(), share).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test() {
+        let message: Vec<Vec<u8>> = (0u8..4u8).map(|i| [i, 1u8, 2u8, 3u8].to_vec()).collect();
+        let (sk, pk): (SecretKey<Config>, PublicKey<Config>) = FSE::gen_key(&mut thread_rng());
+
+        // a seller that corrupts an entry and escrows k with 3 out of 5 agents
+        let (mut offer, k) = FseOffer::sign(&sk, &message, &mut thread_rng());
+        offer.alpha[1] += Fr::from(1u8);
+        let offer = FseOffer::new(&message, offer.alpha, offer.r_g, offer.com_k);
+        let (_, buyer_pk): (SecretKey<Config>, PublicKey<Config>) = FSE::gen_key(&mut thread_rng());
+        let signed = SignedOffer::sign(&sk, offer.clone(), &buyer_pk, 160, &mut thread_rng());
+
+        let agents = agents(k, &offer.com_k);
+
+        // a proof on an entry that does unlock is not upheld, there is no ruling to release on
+        let false_claim = FraudProof::new(&signed, &message, 0, k).unwrap();
+        assert!(Arbiter::rule_on_fraud(&pk, &false_claim).is_none());
+
+        let proof = FraudProof::new(&signed, &message, 1, k).unwrap();
+        let ruling = Arbiter::rule_on_fraud(&pk, &proof).unwrap();
+
+        // nor against a valid proof on another trade
+        let (mut other_offer, other_k) = FseOffer::sign(&sk, &message, &mut thread_rng());
+        other_offer.alpha[1] += Fr::from(1u8);
+        let other_offer = FseOffer::new(&message, other_offer.alpha, other_offer.r_g, other_offer.com_k);
+        let other_signed = SignedOffer::sign(&sk, other_offer, &buyer_pk, 160, &mut thread_rng());
+        let other_proof = FraudProof::new(&other_signed, &message, 1, other_k).unwrap();
+        let other_ruling = Arbiter::rule_on_fraud(&pk, &other_proof).unwrap();
+        assert!(agents.iter().all(|agent| agent.release(&other_ruling).is_none()));
+
+        let released: Vec<_> = [4, 0, 2].iter().map(|i| agents[*i].release(&ruling).unwrap()).collect();
+        assert_eq!(KeyEscrow::reconstruct(&offer.com_k, &released), Some(k));
+        assert_eq!(KeyEscrow::reconstruct(&offer.com_k, &released[..2]), None);
+
+        // duplicated shares do not count twice
+        let duplicated = vec![released[0].clone(), released[0].clone(), released[1].clone()];
+        assert_eq!(KeyEscrow::reconstruct(&offer.com_k, &duplicated), None);
+    }
+
+    #[test]
+    fn test_non_reveal() {
+        let message: Vec<Vec<u8>> = (0u8..4u8).map(|i| [i, 1u8, 2u8, 3u8].to_vec()).collect();
+        let (sk, pk): (SecretKey<Config>, PublicKey<Config>) = FSE::gen_key(&mut thread_rng());
+
+        // the buyer locks the payment, the seller never reveals k
+        let (offer, k) = FseOffer::sign(&sk, &message, &mut thread_rng());
+        let agents = agents(k, &offer.com_k);
+        let mut ledger = Ledger::new(MockClock::new(1_000));
+        ledger.open_account("buyer", 100).unwrap();
+        ledger.open_account("seller", 0).unwrap();
+        let id = ledger.lock("buyer", "seller", 40, offer.com_k, 60).unwrap();
+
+        // no ruling while the seller may still reveal
+        assert_eq!(Arbiter::rule_on_non_reveal(&mut ledger, id).err(), Some(LedgerError::NotExpired));
+
+        // past the deadline the seller is paid and any 3 agents hand k to the buyer
+        ledger.clock().advance(60);
+        let ruling = Arbiter::rule_on_non_reveal(&mut ledger, id).unwrap();
+        assert_eq!(ledger.balance("seller"), Some(40));
+        assert_eq!(ledger.refund(id), Err(LedgerError::NotLocked));
+        assert_eq!(Arbiter::rule_on_non_reveal(&mut ledger, id).err(), Some(LedgerError::NotLocked));
+
+        let released: Vec<_> = [1, 3, 4].iter().map(|i| agents[*i].release(&ruling).unwrap()).collect();
+        let rebuilt = KeyEscrow::reconstruct(&offer.com_k, &released).unwrap();
+        assert_eq!(rebuilt, k);
+        assert!(FSE::recover(&pk, &message, &offer.alpha, &offer.r_g, &offer.com_k, rebuilt).is_ok());
+    }
+
+    #[test]
+    fn test_bad_dealing() {
+        let (_, _, com_k, k) = {
+            let (sk, _): (SecretKey<Config>, PublicKey<Config>) = FSE::gen_key(&mut thread_rng());
+            FSE::sign(&sk, &[[0u8].to_vec()], &mut thread_rng())
+        };
+
+        // a sharing of some other key
+        let (commitments, _) = KeyEscrow::<Config>::deal(k + Fr::from(1u8), 2, 3, &mut thread_rng()).unwrap();
+        assert!(!KeyEscrow::verify_commitments(&com_k, &commitments, 2));
+
+        // a threshold other than the agreed one
+        let (commitments, mut shares) = KeyEscrow::<Config>::deal(k, 2, 3, &mut thread_rng()).unwrap();
+        assert!(KeyEscrow::verify_commitments(&com_k, &commitments, 2));
+        assert!(!KeyEscrow::verify_commitments(&com_k, &commitments, 3));
+
+        // a threshold no set of agents can meet
+        assert_eq!(KeyEscrow::<Config>::deal(k, 0, 3, &mut thread_rng()).err(), Some(EscrowError::InvalidThreshold { t: 0, m: 3 }));
+        assert_eq!(KeyEscrow::<Config>::deal(k, 4, 3, &mut thread_rng()).err(), Some(EscrowError::InvalidThreshold { t: 4, m: 3 }));
+
+        // an agent given a wrong share complains
+        shares[1].value += Fr::from(1u8);
+        assert!(EscrowAgent::new(commitments.clone(), shares[0].clone()).is_some());
+        assert!(EscrowAgent::new(commitments, shares[1].clone()).is_none());
+    }
+}
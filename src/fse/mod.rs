@@ -1 +1,15 @@
-pub mod protocol;
\ No newline at end of file
+pub mod protocol;
+pub mod error;
+pub mod adaptor;
+pub mod payment;
+pub mod tranche;
+pub mod stream;
+pub mod state;
+pub mod offer;
+pub mod spot_check;
+pub mod dispute;
+pub mod signed_offer;
+pub mod multi_seller;
+pub mod escrow;
+pub mod verifiable_escrow;
+pub mod timelock;
\ No newline at end of file
@@ -0,0 +1,265 @@
+use std::marker::PhantomData;
+use std::ops::Mul;
+
+use ark_ec::{CurveConfig, Group};
+use ark_ec::short_weierstrass::{Projective, SWCurveConfig};
+use ark_ff::{PrimeField, Zero};
+use ark_std::UniformRand;
+use rand::Rng;
+
+use crate::fse::error::FseError;
+use crate::fse::protocol::FSE;
+use crate::hash::Hash256;
+use crate::merkle::Digest;
+use crate::schnorr_signature::key::{PublicKey, SecretKey};
+use crate::schnorr_signature::signature::Signature;
+use crate::schnorr_signature::signer::Signer;
+use crate::schnorr_signature::util::{group_element_into_bytes, scalar_into_bytes};
+use crate::schnorr_signature::verifier::Verifier;
+
+const CONTRIBUTION_DOMAIN: &[u8] = b"FSE key contribution";
+
+/// Seller j's part of the joint key: com_k_j = g^{k_j} and a proof of knowledge of k_j,
+/// which keeps a seller from choosing com_k_j as a function of the others' contributions
+#[derive(Clone)]
+pub struct KeyContribution<G1>
+where
+    G1: SWCurveConfig + Clone,
+    G1::ScalarField: PrimeField,
+{
+    pub com_k: Projective<G1>,
+    pub pok: Signature<G1>,
+}
+
+impl<G1> KeyContribution<G1>
+where
+    G1: SWCurveConfig + Clone,
+    G1::ScalarField: PrimeField,
+    <G1 as CurveConfig>::BaseField: PrimeField,
+{
+    /// The proof of knowledge is a Schnorr signature under com_k_j, bound to the seller's key
+    fn statement(seller_pk: &PublicKey<G1>) -> Vec<u8> {
+        let mut bytes = CONTRIBUTION_DOMAIN.to_vec();
+        bytes.extend(group_element_into_bytes::<G1>(&seller_pk.pk));
+        bytes
+    }
+
+    /// Hash published in the commit round, before any contribution is opened
+    pub fn commitment(&self) -> Digest {
+        let mut bytes = group_element_into_bytes::<G1>(&self.com_k);
+        bytes.extend(group_element_into_bytes::<G1>(&self.pok.r_g));
+        bytes.extend(scalar_into_bytes(&self.pok.s));
+        Hash256::digest(&bytes)
+    }
+
+    pub fn verify(&self, seller_pk: &PublicKey<G1>) -> bool {
+        Verifier::new(PublicKey { pk: self.com_k }).verify(&Self::statement(seller_pk), &self.pok)
+    }
+}
+
+/// One seller's side of the key generation: publish the commitment, then the contribution,
+/// then hand k_j privately to the other sellers so that every seller learns k = sum of k_j
+pub struct KeyDealer<G1>
+where
+    G1: SWCurveConfig + Clone,
+    G1::ScalarField: PrimeField,
+{
+    k: G1::ScalarField,
+    contribution: KeyContribution<G1>,
+}
+
+impl<G1> KeyDealer<G1>
+where
+    G1: SWCurveConfig + Clone,
+    G1::ScalarField: PrimeField,
+    <G1 as CurveConfig>::BaseField: PrimeField,
+{
+    pub fn new<R: Rng>(seller_pk: &PublicKey<G1>, rng: &mut R) -> Self {
+        let k = G1::ScalarField::rand(rng);
+        let pok = Signer::new(SecretKey { sk: k }).sign(&KeyContribution::statement(seller_pk), rng);
+        let contribution = KeyContribution { com_k: Projective::generator().mul(k), pok };
+        KeyDealer { k, contribution }
+    }
+
+    pub fn commitment(&self) -> Digest {
+        self.contribution.commitment()
+    }
+
+    pub fn contribution(&self) -> &KeyContribution<G1> {
+        &self.contribution
+    }
+
+    /// k_j, only ever sent to the other sellers
+    pub fn share(&self) -> G1::ScalarField {
+        self.k
+    }
+}
+
+/// FSE across several sellers, each signing its own messages under its own key
+/// but masking them with the same jointly generated k
+pub struct MultiSellerFSE<G1>
+where
+    G1: SWCurveConfig + Clone,
+    G1::ScalarField: PrimeField,
+{
+    phantom: PhantomData<G1>,
+}
+
+impl<G1> MultiSellerFSE<G1>
+where
+    G1: SWCurveConfig + Clone,
+    G1::ScalarField: PrimeField,
+    <G1 as CurveConfig>::BaseField: PrimeField,
+{
+    /// com_k as the sum of the contributions, None if a contribution does not open its
+    /// commitment or lacks a valid proof of knowledge
+    pub fn joint_com_k(seller_pk: &[PublicKey<G1>], commitment: &[Digest], contribution: &[KeyContribution<G1>]) -> Option<Projective<G1>> {
+        if seller_pk.is_empty() || commitment.len() != seller_pk.len() || contribution.len() != seller_pk.len() {
+            return None;
+        }
+
+        let mut com_k = Projective::zero();
+        for ((pk_j, commitment_j), contribution_j) in seller_pk.iter().zip(commitment).zip(contribution) {
+            if contribution_j.commitment() != *commitment_j || !contribution_j.verify(pk_j) {
+                return None;
+            }
+            com_k += contribution_j.com_k;
+        }
+        Some(com_k)
+    }
+
+    /// Seller: k from the shares received from every seller, None if a share does not match its contribution
+    pub fn joint_key(contribution: &[KeyContribution<G1>], share: &[G1::ScalarField]) -> Option<G1::ScalarField> {
+        if contribution.len() != share.len() {
+            return None;
+        }
+
+        let mut k = G1::ScalarField::zero();
+        for (contribution_j, k_j) in contribution.iter().zip(share) {
+            if Projective::generator().mul(*k_j) != contribution_j.com_k {
+                return None;
+            }
+            k += k_j;
+        }
+        Some(k)
+    }
+
+    /// Seller: mask the signatures on its messages with the joint k
+    pub fn sign<R: Rng>(sk: &SecretKey<G1>, message: &[Vec<u8>], k: G1::ScalarField, rng: &mut R) -> (Vec<G1::ScalarField>, Vec<Projective<G1>>) {
+        let (alpha, r_g, _) = FSE::sign_with_key(sk, message, k, rng);
+        (alpha, r_g)
+    }
+
+    /// Buyer: check every seller's offer against the same com_k
+    pub fn verify(pk: &[PublicKey<G1>],
+                  message: &[Vec<Vec<u8>>],
+                  alpha: &[Vec<G1::ScalarField>],
+                  r_g: &[Vec<Projective<G1>>],
+                  com_k: &Projective<G1>,
+    ) -> bool {
+        let m = pk.len();
+        if message.len() != m || alpha.len() != m || r_g.len() != m {
+            return false;
+        }
+
+        (0..m).all(|j| FSE::verify(&pk[j], &message[j], &alpha[j], &r_g[j], com_k))
+    }
+
+    /// Buyer: recover the signatures of every seller from the single revealed k
+    #[allow(clippy::type_complexity)]
+    pub fn recover(pk: &[PublicKey<G1>],
+                   message: &[Vec<Vec<u8>>],
+                   alpha: &[Vec<G1::ScalarField>],
+                   r_g: &[Vec<Projective<G1>>],
+                   com_k: &Projective<G1>,
+                   k: G1::ScalarField,
+    ) -> Vec<Result<Vec<Signature<G1>>, FseError>> {
+        let m = pk.len();
+        if message.len() != m || alpha.len() != m || r_g.len() != m {
+            return (0..m).map(|_| Err(FseError::LengthMismatch)).collect();
+        }
+
+        (0..m).map(|j| FSE::recover(&pk[j], &message[j], &alpha[j], &r_g[j], com_k, k)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ops::{Mul, Sub};
+
+    use ark_bn254::g1::Config;
+    use ark_bn254::Fr;
+    use ark_ec::Group;
+    use ark_ec::short_weierstrass::Projective;
+    use rand::thread_rng;
+
+    use crate::fse::error::FseError;
+    use crate::fse::multi_seller::{KeyContribution, KeyDealer, MultiSellerFSE};
+    use crate::fse::protocol::FSE;
+    use crate::schnorr_signature::key::{PublicKey, SecretKey};
+    use crate::schnorr_signature::verifier::Verifier;
+
+    #[test]
+    fn test() {
+        let keys: Vec<(SecretKey<Config>, PublicKey<Config>)> = (0..3).map(|_| FSE::gen_key(&mut thread_rng())).collect();
+        let pk: Vec<PublicKey<Config>> = keys.iter().map(|(_, pk)| pk.clone()).collect();
+        let message: Vec<Vec<Vec<u8>>> = (0u8..3u8)
+            .map(|j| (0u8..j + 2).map(|i| [j, i, 2u8, 3u8].to_vec()).collect())
+            .collect();
+
+        // commit, open, then exchange the shares among the sellers
+        let dealers: Vec<KeyDealer<Config>> = pk.iter().map(|pk_j| KeyDealer::new(pk_j, &mut thread_rng())).collect();
+        let commitment: Vec<_> = dealers.iter().map(|d| d.commitment()).collect();
+        let contribution: Vec<_> = dealers.iter().map(|d| d.contribution().clone()).collect();
+        let share: Vec<Fr> = dealers.iter().map(|d| d.share()).collect();
+        let k = MultiSellerFSE::joint_key(&contribution, &share).unwrap();
+
+        let (alpha, r_g): (Vec<_>, Vec<_>) = keys.iter().zip(&message)
+            .map(|((sk, _), m_j)| MultiSellerFSE::sign(sk, m_j, k, &mut thread_rng()))
+            .unzip();
+
+        // the buyer derives com_k from the contributions and checks every offer against it
+        let com_k = MultiSellerFSE::joint_com_k(&pk, &commitment, &contribution).unwrap();
+        assert_eq!(com_k, Projective::generator().mul(k));
+        assert!(MultiSellerFSE::verify(&pk, &message, &alpha, &r_g, &com_k));
+
+        // one reveal unlocks every seller's signatures
+        for (j, recovered) in MultiSellerFSE::recover(&pk, &message, &alpha, &r_g, &com_k, k).into_iter().enumerate() {
+            let verifier = Verifier::new(pk[j].clone());
+            for (m_i, sig) in message[j].iter().zip(recovered.unwrap()) {
+                assert!(verifier.verify(m_i, &sig));
+            }
+        }
+
+        let recovered = MultiSellerFSE::recover(&pk, &message, &alpha, &r_g, &com_k, share[0]);
+        assert!(recovered.iter().all(|r| matches!(r, Err(FseError::InvalidKey))));
+    }
+
+    #[test]
+    fn test_bad_contribution() {
+        let pk: Vec<PublicKey<Config>> = (0..2).map(|_| FSE::gen_key(&mut thread_rng()).1).collect();
+        let dealers: Vec<KeyDealer<Config>> = pk.iter().map(|pk_j| KeyDealer::new(pk_j, &mut thread_rng())).collect();
+        let commitment: Vec<_> = dealers.iter().map(|d| d.commitment()).collect();
+        let contribution: Vec<_> = dealers.iter().map(|d| d.contribution().clone()).collect();
+        assert!(MultiSellerFSE::joint_com_k(&pk, &commitment, &contribution).is_some());
+
+        // contributions are bound to their seller
+        let swapped = vec![pk[1].clone(), pk[0].clone()];
+        assert!(MultiSellerFSE::joint_com_k(&swapped, &commitment, &contribution).is_none());
+
+        // a rogue contribution cancelling the other one comes without a proof of knowledge
+        let (_, target): (SecretKey<Config>, PublicKey<Config>) = FSE::gen_key(&mut thread_rng());
+        let rogue = KeyContribution { com_k: target.pk.sub(contribution[0].com_k), pok: contribution[1].pok.clone() };
+        let mut opened = contribution.clone();
+        opened[1] = rogue.clone();
+        let recommitted = vec![commitment[0], rogue.commitment()];
+        assert!(MultiSellerFSE::joint_com_k(&pk, &recommitted, &opened).is_none());
+
+        // an opening that differs from the commitment
+        assert!(MultiSellerFSE::joint_com_k(&pk, &commitment, &opened).is_none());
+
+        // a share that does not match its contribution
+        let share = vec![dealers[0].share(), dealers[0].share()];
+        assert!(MultiSellerFSE::joint_key(&contribution, &share).is_none());
+    }
+}
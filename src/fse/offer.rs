@@ -0,0 +1,146 @@
+use ark_ec::CurveConfig;
+use ark_ec::short_weierstrass::{Projective, SWCurveConfig};
+use ark_ff::PrimeField;
+use rand::Rng;
+
+use crate::fse::protocol::FSE;
+use crate::hash::Hash256;
+use crate::merkle::{Digest, hash_leaf, MerkleProof, MerkleTree};
+use crate::schnorr_signature::key::{PublicKey, SecretKey};
+use crate::schnorr_signature::util::{group_element_into_bytes, scalar_into_bytes};
+
+/// FSE offer committed to by a Merkle root over (i, R_i, alpha_i, m_i),
+/// so that a single signature can later be shown to belong to the offer
+#[derive(Clone, Debug)]
+pub struct FseOffer<G1>
+where
+    G1: SWCurveConfig + Clone,
+    G1::ScalarField: PrimeField,
+{
+    pub alpha: Vec<G1::ScalarField>,
+    pub r_g: Vec<Projective<G1>>,
+    pub com_k: Projective<G1>,
+    pub root: Digest,
+}
+
+impl<G1> FseOffer<G1>
+where
+    G1: SWCurveConfig + Clone,
+    G1::ScalarField: PrimeField,
+    <G1 as CurveConfig>::BaseField: PrimeField,
+{
+    pub fn new(message: &[Vec<u8>], alpha: Vec<G1::ScalarField>, r_g: Vec<Projective<G1>>, com_k: Projective<G1>) -> Self {
+        let root = Self::tree(message, &alpha, &r_g).root();
+        FseOffer { alpha, r_g, com_k, root }
+    }
+
+    /// Sign the messages with FSE and commit to the result, returns the offer and k
+    pub fn sign<R: Rng>(sk: &SecretKey<G1>, message: &[Vec<u8>], rng: &mut R) -> (Self, G1::ScalarField) {
+        let (alpha, r_g, com_k, k) = FSE::sign(sk, message, rng);
+        (Self::new(message, alpha, r_g, com_k), k)
+    }
+
+    pub fn len(&self) -> usize {
+        self.alpha.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.alpha.is_empty()
+    }
+
+    /// Leaf of index i, binding its position, R_i, alpha_i and m_i
+    pub fn leaf(index: usize, message: &[u8], r_g: &Projective<G1>, alpha: &G1::ScalarField) -> Digest {
+        let mut bytes = (index as u64).to_le_bytes().to_vec();
+        bytes.extend(group_element_into_bytes::<G1>(r_g));
+        bytes.extend(scalar_into_bytes(alpha));
+        bytes.extend_from_slice(message);
+        hash_leaf(&bytes)
+    }
+
+    fn tree(message: &[Vec<u8>], alpha: &[G1::ScalarField], r_g: &[Projective<G1>]) -> MerkleTree {
+        let leaves = message.iter().zip(r_g.iter().zip(alpha)).enumerate()
+            .map(|(i, (m_i, (r_g_i, alpha_i)))| Self::leaf(i, m_i, r_g_i, alpha_i))
+            .collect();
+        MerkleTree::new(leaves)
+    }
+
+    /// 32 bytes identifying the whole offer, the root together with com_k
+    pub fn digest(&self) -> Digest {
+        Self::digest_of(&self.root, &self.com_k, self.len())
+    }
+
+    /// Offer digest from its parts, for parties that only hold the root
+    pub fn digest_of(root: &Digest, com_k: &Projective<G1>, n: usize) -> Digest {
+        let mut bytes = root.to_vec();
+        bytes.extend(group_element_into_bytes::<G1>(com_k));
+        bytes.extend((n as u64).to_le_bytes());
+        Hash256::digest(&bytes)
+    }
+
+    /// Check the masked signatures and that the root commits to exactly these messages
+    pub fn verify(&self, pk: &PublicKey<G1>, message: &[Vec<u8>]) -> bool {
+        FSE::verify(pk, message, &self.alpha, &self.r_g, &self.com_k) && self.verify_root(message)
+    }
+
+    /// Check only that the root commits to these messages and the offer's R and alpha values
+    pub fn verify_root(&self, message: &[Vec<u8>]) -> bool {
+        message.len() == self.len() && self.r_g.len() == self.len() && Self::tree(message, &self.alpha, &self.r_g).root() == self.root
+    }
+
+    pub fn prove(&self, message: &[Vec<u8>], index: usize) -> Option<MerkleProof> {
+        if message.len() != self.len() || self.r_g.len() != self.len() {
+            return None;
+        }
+        Self::tree(message, &self.alpha, &self.r_g).prove(index)
+    }
+
+    /// Check that (m_i, R_i, alpha_i) is the entry at proof.index of the offer committed to by root
+    pub fn verify_inclusion(root: &Digest, message: &[u8], r_g: &Projective<G1>, alpha: &G1::ScalarField, proof: &MerkleProof) -> bool {
+        MerkleTree::verify(root, &Self::leaf(proof.index, message, r_g, alpha), proof)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_bn254::g1::Config;
+    use ark_bn254::Fr;
+    use rand::thread_rng;
+
+    use crate::fse::offer::FseOffer;
+    use crate::fse::protocol::FSE;
+    use crate::schnorr_signature::key::{PublicKey, SecretKey};
+
+    #[test]
+    fn test() {
+        let message: Vec<Vec<u8>> = (0u8..5u8).map(|i| [i, 1u8, 2u8, 3u8].to_vec()).collect();
+        let (sk, pk): (SecretKey<Config>, PublicKey<Config>) = FSE::gen_key(&mut thread_rng());
+
+        let (offer, _) = FseOffer::sign(&sk, &message, &mut thread_rng());
+        assert!(offer.verify(&pk, &message));
+
+        for i in 0..message.len() {
+            let proof = offer.prove(&message, i).unwrap();
+            assert!(FseOffer::verify_inclusion(&offer.root, &message[i], &offer.r_g[i], &offer.alpha[i], &proof));
+            // the entry is bound to its position and content
+            assert!(!FseOffer::verify_inclusion(&offer.root, &[9u8], &offer.r_g[i], &offer.alpha[i], &proof));
+            assert!(!FseOffer::verify_inclusion(&offer.root, &message[i], &offer.r_g[i], &(offer.alpha[i] + Fr::from(1u8)), &proof));
+        }
+
+        // a different message set does not match the root
+        let mut other = message.clone();
+        other.swap(0, 1);
+        assert!(!offer.verify(&pk, &other));
+        assert!(offer.prove(&message[1..], 0).is_none());
+    }
+
+    #[test]
+    fn test_digest() {
+        let message = vec![[0u8, 1u8, 2u8, 3u8].to_vec()];
+        let (sk, _): (SecretKey<Config>, PublicKey<Config>) = FSE::gen_key(&mut thread_rng());
+
+        let (offer, _) = FseOffer::sign(&sk, &message, &mut thread_rng());
+        let mut tampered = offer.clone();
+        tampered.com_k = offer.r_g[0];
+        assert_ne!(offer.digest(), tampered.digest());
+    }
+}
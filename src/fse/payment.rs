@@ -0,0 +1,135 @@
+use std::marker::PhantomData;
+use std::ops::Mul;
+
+use ark_ec::{CurveConfig, Group};
+use ark_ec::short_weierstrass::{Projective, SWCurveConfig};
+use ark_ff::PrimeField;
+use rand::Rng;
+
+use crate::fse::error::FseError;
+use crate::fse::protocol::FSE;
+use crate::schnorr_signature::adaptor::{adapt, extract};
+use crate::schnorr_signature::key::{PublicKey, SecretKey};
+use crate::schnorr_signature::signature::{PreSignature, Signature};
+use crate::schnorr_signature::signer::Signer;
+use crate::schnorr_signature::verifier::Verifier;
+
+/// Pay for an FSE offer with an adaptor signature locked to com_k:
+/// the buyer pre-signs the payment under T = com_k, the seller can only claim it
+/// by completing the pre-signature with k, and the claimed signature hands k to the buyer
+pub struct FsePayment<G1>
+where
+    G1: SWCurveConfig + Clone,
+    G1::ScalarField: PrimeField,
+{
+    phantom: PhantomData<G1>,
+}
+
+impl<G1> FsePayment<G1>
+where
+    G1: SWCurveConfig + Clone,
+    G1::ScalarField: PrimeField,
+{
+    /// Buyer: pre-sign the payment message, locked to the offer's com_k
+    pub fn lock<R: Rng>(buyer_sk: &SecretKey<G1>, payment: &[u8], com_k: &Projective<G1>, rng: &mut R) -> PreSignature<G1>
+    where
+        <G1 as CurveConfig>::BaseField: PrimeField,
+    {
+        Signer::new(buyer_sk.clone()).pre_sign(payment, com_k, rng)
+    }
+
+    /// Seller: check the locked payment before revealing anything
+    pub fn verify_lock(buyer_pk: &PublicKey<G1>, payment: &[u8], com_k: &Projective<G1>, pre_signature: &PreSignature<G1>) -> bool
+    where
+        <G1 as CurveConfig>::BaseField: PrimeField,
+    {
+        Verifier::new(buyer_pk.clone()).pre_verify(payment, com_k, pre_signature)
+    }
+
+    /// Seller: complete the payment signature with k in order to claim it
+    pub fn claim(pre_signature: &PreSignature<G1>, k: G1::ScalarField) -> Signature<G1> {
+        adapt(pre_signature, k)
+    }
+
+    /// Buyer: learn k from the claimed payment signature, checking it against com_k
+    pub fn reveal_key(pre_signature: &PreSignature<G1>, claimed: &Signature<G1>, com_k: &Projective<G1>) -> Option<G1::ScalarField> {
+        extract(pre_signature, claimed).filter(|k| Projective::generator().mul(*k) == *com_k)
+    }
+
+    /// Buyer: extract k from the claimed payment and recover the signatures of the offer
+    pub fn open(pre_signature: &PreSignature<G1>,
+                claimed: &Signature<G1>,
+                seller_pk: &PublicKey<G1>,
+                message: &[Vec<u8>],
+                alpha: &[G1::ScalarField],
+                r_g: &[Projective<G1>],
+                com_k: &Projective<G1>,
+    ) -> Result<Vec<Signature<G1>>, FseError>
+    where
+        <G1 as CurveConfig>::BaseField: PrimeField,
+    {
+        let k = Self::reveal_key(pre_signature, claimed, com_k).ok_or(FseError::InvalidKey)?;
+        FSE::recover(seller_pk, message, alpha, r_g, com_k, k)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_bn254::g1::Config;
+    use ark_bn254::Fr;
+    use rand::thread_rng;
+
+    use crate::fse::error::FseError;
+    use crate::fse::payment::FsePayment;
+    use crate::fse::protocol::FSE;
+    use crate::schnorr_signature::key::{PublicKey, SecretKey};
+    use crate::schnorr_signature::verifier::Verifier;
+
+    #[test]
+    fn test() {
+        let message = vec![
+            [0u8, 1u8, 2u8, 3u8].to_vec(),
+            [4u8, 5u8, 6u8, 7u8].to_vec(),
+        ];
+        let payment = b"pay 10 coins to seller".to_vec();
+
+        let (seller_sk, seller_pk): (SecretKey<Config>, PublicKey<Config>) = FSE::gen_key(&mut thread_rng());
+        let (buyer_sk, buyer_pk): (SecretKey<Config>, PublicKey<Config>) = FSE::gen_key(&mut thread_rng());
+
+        // seller offers, buyer checks the offer
+        let (vec_alpha, r_g, com_k, k) = FSE::sign(&seller_sk, &message, &mut thread_rng());
+        assert!(FSE::verify(&seller_pk, &message, &vec_alpha, &r_g, &com_k));
+
+        // buyer locks the payment to com_k, seller checks the lock
+        let pre_signature = FsePayment::lock(&buyer_sk, &payment, &com_k, &mut thread_rng());
+        assert!(FsePayment::verify_lock(&buyer_pk, &payment, &com_k, &pre_signature));
+
+        // seller claims, which publishes a valid payment signature
+        let claimed = FsePayment::claim(&pre_signature, k);
+        assert!(Verifier::new(buyer_pk.clone()).verify(&payment, &claimed));
+
+        // buyer extracts k from the claim and recovers the signatures
+        let signatures = FsePayment::open(&pre_signature, &claimed, &seller_pk, &message, &vec_alpha, &r_g, &com_k).unwrap();
+        let verifier = Verifier::new(seller_pk.clone());
+        for (i, sig) in signatures.iter().enumerate() {
+            assert!(verifier.verify(&message[i], sig));
+        }
+    }
+
+    #[test]
+    fn test_claim_with_wrong_key() {
+        let message = vec![[0u8, 1u8, 2u8, 3u8].to_vec()];
+        let payment = b"pay 10 coins to seller".to_vec();
+
+        let (seller_sk, seller_pk): (SecretKey<Config>, PublicKey<Config>) = FSE::gen_key(&mut thread_rng());
+        let (buyer_sk, buyer_pk): (SecretKey<Config>, PublicKey<Config>) = FSE::gen_key(&mut thread_rng());
+
+        let (vec_alpha, r_g, com_k, k) = FSE::sign(&seller_sk, &message, &mut thread_rng());
+        let pre_signature = FsePayment::lock(&buyer_sk, &payment, &com_k, &mut thread_rng());
+
+        // a claim that does not use k is not a valid payment, and reveals nothing usable
+        let claimed = FsePayment::claim(&pre_signature, k + Fr::from(1u8));
+        assert!(!Verifier::new(buyer_pk).verify(&payment, &claimed));
+        assert_eq!(FsePayment::open(&pre_signature, &claimed, &seller_pk, &message, &vec_alpha, &r_g, &com_k).err(), Some(FseError::InvalidKey));
+    }
+}
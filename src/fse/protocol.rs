@@ -9,10 +9,12 @@ use rand::Rng;
 use rayon::iter::{IntoParallelIterator, IntoParallelRefIterator};
 use rayon::iter::ParallelIterator;
 
+use crate::fse::error::FseError;
 use crate::hash::Hash256;
 use crate::schnorr_signature::key::{generate_key_pair, PublicKey, SecretKey};
 use crate::schnorr_signature::signature::Signature;
 use crate::schnorr_signature::util::group_element_into_bytes;
+use crate::schnorr_signature::verifier::Verifier;
 
 pub struct FSE<G1>
 where
@@ -31,7 +33,19 @@ where
         generate_key_pair(rng)
     }
 
-    pub fn sign<R: Rng>(sk: &SecretKey<G1>, message: &Vec<Vec<u8>>, rng: &mut R) -> (Vec<G1::ScalarField>, Vec<Projective<G1>>, Projective<G1>, G1::ScalarField)
+    #[allow(clippy::type_complexity)]
+    pub fn sign<R: Rng>(sk: &SecretKey<G1>, message: &[Vec<u8>], rng: &mut R) -> (Vec<G1::ScalarField>, Vec<Projective<G1>>, Projective<G1>, G1::ScalarField)
+    where
+        <G1 as CurveConfig>::BaseField: PrimeField,
+    {
+        let k = G1::ScalarField::rand(rng);
+        let (vec_alpha, r_g, g_k) = Self::sign_with_key(sk, message, k, rng);
+
+        (vec_alpha, r_g, g_k, k)
+    }
+
+    /// Same as sign, but masks the signatures with a caller-provided k
+    pub fn sign_with_key<R: Rng>(sk: &SecretKey<G1>, message: &[Vec<u8>], k: G1::ScalarField, rng: &mut R) -> (Vec<G1::ScalarField>, Vec<Projective<G1>>, Projective<G1>)
     where
         <G1 as CurveConfig>::BaseField: PrimeField,
     {
@@ -40,7 +54,6 @@ where
         let r: Vec<G1::ScalarField> = (0..n).map(|_| G1::ScalarField::rand(rng)).collect();
         let r_g: Vec<Projective<G1>> = r.par_iter().map(|r_i| Projective::generator().mul(*r_i)).collect();
 
-        let k = G1::ScalarField::rand(rng);
         let g_k = Projective::generator().mul(k);
 
         // Parallelized the computation of vec_c using rayon's par_iter
@@ -52,19 +65,20 @@ where
             };
 
             let s_i = r[i] + c_i * sk.sk;
-            let alpha_i = (s_i + k) / G1::ScalarField::from(2u8);
-
-            alpha_i
+            (s_i + k) / G1::ScalarField::from(2u8)
         }).collect();
 
-        (vec_alpha, r_g, g_k, k)
+        (vec_alpha, r_g, g_k)
     }
 
-    pub fn verify(pk: &PublicKey<G1>, message: &Vec<Vec<u8>>, alpha: &Vec<G1::ScalarField>, r_g: &Vec<Projective<G1>>, com_k: &Projective<G1>)
+    pub fn verify(pk: &PublicKey<G1>, message: &[Vec<u8>], alpha: &[G1::ScalarField], r_g: &[Projective<G1>], com_k: &Projective<G1>) -> bool
     where
         <G1 as CurveConfig>::BaseField: PrimeField,
     {
         let n = message.len();
+        if alpha.len() != n || r_g.len() != n {
+            return false;
+        }
 
         // Parallelized the computation of vec_c using rayon's par_iter
         let vec_c: Vec<G1::ScalarField> = (0..n).into_par_iter().map(|i| {
@@ -76,59 +90,108 @@ where
             c_i
         }).collect();
 
-        for i in 0..n {
+        (0..n).all(|i| {
             let com_i = r_g[i].add(pk.pk.mul(vec_c[i]));
-            assert_eq!(
-                Projective::generator().mul(G1::ScalarField::from(2u128) * alpha[i]),
-                com_k.add(com_i)
-            );
-        }
+            Projective::generator().mul(G1::ScalarField::from(2u128) * alpha[i]) == com_k.add(com_i)
+        })
     }
 
-    pub fn recover(alpha: &Vec<G1::ScalarField>, r_g: &Vec<Projective<G1>>, k: G1::ScalarField) -> Vec<Signature<G1>> {
-        let signatures: Vec<Signature<G1>> = {
-            let mut res = Vec::new();
-            for i in 0..alpha.len() {
-                let s_i = G1::ScalarField::from(2u128) * alpha[i] - k;
-                res.push(Signature {
-                    r_g: r_g[i],
-                    s: s_i,
-                });
+    /// Unmask the signatures with the revealed k, checking k against com_k
+    /// and the signatures against pk and the messages
+    pub fn recover(pk: &PublicKey<G1>,
+                   message: &[Vec<u8>],
+                   alpha: &[G1::ScalarField],
+                   r_g: &[Projective<G1>],
+                   com_k: &Projective<G1>,
+                   k: G1::ScalarField,
+    ) -> Result<Vec<Signature<G1>>, FseError>
+    where
+        <G1 as CurveConfig>::BaseField: PrimeField,
+    {
+        let n = message.len();
+        if alpha.len() != n || r_g.len() != n {
+            return Err(FseError::LengthMismatch);
+        }
+        if Projective::generator().mul(k) != *com_k {
+            return Err(FseError::InvalidKey);
+        }
+
+        let signatures: Vec<Signature<G1>> = (0..n).map(|i| {
+            Signature {
+                r_g: r_g[i],
+                s: G1::ScalarField::from(2u128) * alpha[i] - k,
             }
-            res
-        };
+        }).collect();
+
+        // batch check first, only look for the culprits if it fails
+        let verifier = Verifier::new(pk.clone());
+        if !verifier.batch_verify(message, &signatures) {
+            let bad: Vec<usize> = (0..n).into_par_iter()
+                .filter(|i| !verifier.verify(&message[*i], &signatures[*i]))
+                .collect();
+            return Err(FseError::InvalidSignatures(bad));
+        }
 
-        signatures
+        Ok(signatures)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use ark_bn254::g1::Config;
+    use ark_bn254::Fr;
+    use ark_ec::short_weierstrass::Projective;
+    use ark_ff::Zero;
     use rand::thread_rng;
 
+    use crate::fse::error::FseError;
     use crate::fse::protocol::FSE;
     use crate::schnorr_signature::key::{PublicKey, SecretKey};
     use crate::schnorr_signature::verifier::Verifier;
 
     #[test]
     fn test() {
-        let message = {
-            let mut temp = Vec::new();
-            temp.push([0u8, 1u8, 2u8, 3u8].to_vec());
-            temp.push([0u8, 1u8, 2u8, 3u8].to_vec());
-            temp
-        };
+        let message = vec![
+            [0u8, 1u8, 2u8, 3u8].to_vec(),
+            [0u8, 1u8, 2u8, 3u8].to_vec(),
+        ];
         let (sk, pk): (SecretKey<Config>, PublicKey<Config>) = FSE::gen_key(&mut thread_rng());
 
         let (vec_alpha, r_g, g_k, k) = FSE::sign(&sk, &message, &mut thread_rng());
-        FSE::verify(&pk, &message, &vec_alpha, &r_g, &g_k);
+        assert!(FSE::verify(&pk, &message, &vec_alpha, &r_g, &g_k));
 
-        let signatures = FSE::recover(&vec_alpha, &r_g, k);
+        let signatures = FSE::recover(&pk, &message, &vec_alpha, &r_g, &g_k, k).unwrap();
 
         for (i, sig) in signatures.iter().enumerate() {
             let verifier = Verifier::new(pk.clone());
             assert!(verifier.verify(&message[i], sig));
         }
     }
-}
+
+    #[test]
+    fn test_recover_rejects_bad_key_and_signatures() {
+        let message: Vec<Vec<u8>> = (0u8..4u8).map(|i| [i, 1u8, 2u8, 3u8].to_vec()).collect();
+        let (sk, pk): (SecretKey<Config>, PublicKey<Config>) = FSE::gen_key(&mut thread_rng());
+
+        let (mut vec_alpha, r_g, g_k, k) = FSE::sign(&sk, &message, &mut thread_rng());
+        assert_eq!(FSE::recover(&pk, &message, &vec_alpha, &r_g, &g_k, k + Fr::from(1u8)).err(), Some(FseError::InvalidKey));
+        assert_eq!(FSE::recover(&pk, &message[1..], &vec_alpha, &r_g, &g_k, k).err(), Some(FseError::LengthMismatch));
+
+        vec_alpha[1] += Fr::from(1u8);
+        vec_alpha[3] += Fr::from(1u8);
+        assert_eq!(FSE::recover(&pk, &message, &vec_alpha, &r_g, &g_k, k).err(), Some(FseError::InvalidSignatures(vec![1, 3])));
+    }
+
+    #[test]
+    fn test_identity_points() {
+        let message: Vec<Vec<u8>> = (0u8..2u8).map(|i| [i, 1u8, 2u8, 3u8].to_vec()).collect();
+        let (sk, pk): (SecretKey<Config>, PublicKey<Config>) = FSE::gen_key(&mut thread_rng());
+
+        // a nonce or commitment at infinity is refused rather than crashing the buyer
+        let (vec_alpha, mut r_g, g_k, k) = FSE::sign(&sk, &message, &mut thread_rng());
+        r_g[1] = Projective::zero();
+        assert!(!FSE::verify(&pk, &message, &vec_alpha, &r_g, &g_k));
+        assert!(!FSE::verify(&pk, &message, &vec_alpha, &r_g, &Projective::zero()));
+        assert_eq!(FSE::recover(&pk, &message, &vec_alpha, &r_g, &g_k, k).err(), Some(FseError::InvalidSignatures(vec![1])));
+    }
+}
\ No newline at end of file
@@ -0,0 +1,188 @@
+use std::fmt;
+
+use ark_ec::CurveConfig;
+use ark_ec::short_weierstrass::SWCurveConfig;
+use ark_ff::PrimeField;
+use rand::Rng;
+
+use crate::clock::Clock;
+use crate::fse::offer::FseOffer;
+use crate::merkle::Digest;
+use crate::schnorr_signature::key::{PublicKey, SecretKey};
+use crate::schnorr_signature::signature::Signature;
+use crate::schnorr_signature::signer::Signer;
+use crate::schnorr_signature::util::group_element_into_bytes;
+use crate::schnorr_signature::verifier::Verifier;
+
+const OFFER_DOMAIN: &[u8] = b"FSE offer";
+
+/// Bytes signed by the seller: offer digest, buyer public key, expiry and nonce
+fn offer_statement<G1>(digest: &Digest, buyer: &PublicKey<G1>, expiry: u64, nonce: &[u8; 32]) -> Vec<u8>
+where
+    G1: SWCurveConfig + Clone,
+    G1::ScalarField: PrimeField,
+    <G1 as CurveConfig>::BaseField: PrimeField,
+{
+    let mut bytes = OFFER_DOMAIN.to_vec();
+    bytes.extend_from_slice(digest);
+    bytes.extend(group_element_into_bytes::<G1>(&buyer.pk));
+    bytes.extend(expiry.to_le_bytes());
+    bytes.extend_from_slice(nonce);
+    bytes
+}
+
+/// Seller: sign the offer for `buyer`, valid until `expiry`, with the long-term key,
+/// making the offer attributable
+pub fn sign_offer<G1, R>(sk: &SecretKey<G1>, offer: &FseOffer<G1>, buyer: &PublicKey<G1>, expiry: u64, nonce: &[u8; 32], rng: &mut R) -> Signature<G1>
+where
+    G1: SWCurveConfig + Clone,
+    G1::ScalarField: PrimeField,
+    <G1 as CurveConfig>::BaseField: PrimeField,
+    R: Rng,
+{
+    Signer::new(sk.clone()).sign(&offer_statement(&offer.digest(), buyer, expiry, nonce), rng)
+}
+
+pub fn verify_offer_signature<G1>(pk: &PublicKey<G1>, digest: &Digest, buyer: &PublicKey<G1>, expiry: u64, nonce: &[u8; 32], signature: &Signature<G1>) -> bool
+where
+    G1: SWCurveConfig + Clone,
+    G1::ScalarField: PrimeField,
+    <G1 as CurveConfig>::BaseField: PrimeField,
+{
+    Verifier::new(pk.clone()).verify(&offer_statement(digest, buyer, expiry, nonce), signature)
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum OfferError {
+    /// The offer was made out to another buyer
+    WrongBuyer,
+    /// The seller's signature does not cover these terms
+    InvalidSignature,
+    /// The current time is at or past the expiry
+    Expired,
+    /// The masked signatures or the root do not match the messages
+    InvalidOffer,
+}
+
+impl fmt::Display for OfferError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OfferError::WrongBuyer => write!(f, "offer is addressed to another buyer"),
+            OfferError::InvalidSignature => write!(f, "invalid seller signature on the offer"),
+            OfferError::Expired => write!(f, "offer has expired"),
+            OfferError::InvalidOffer => write!(f, "offer does not verify against the messages"),
+        }
+    }
+}
+
+impl std::error::Error for OfferError {}
+
+/// FSE offer authenticated by the seller's long-term key and bound to one buyer, an
+/// expiry time and a fresh nonce, so it cannot be redirected, replayed or used after it
+/// lapses. The signature is the one a FraudProof carries
+#[derive(Clone)]
+pub struct SignedOffer<G1>
+where
+    G1: SWCurveConfig + Clone,
+    G1::ScalarField: PrimeField,
+{
+    pub offer: FseOffer<G1>,
+    pub buyer: PublicKey<G1>,
+    pub expiry: u64,
+    pub nonce: [u8; 32],
+    pub signature: Signature<G1>,
+}
+
+impl<G1> SignedOffer<G1>
+where
+    G1: SWCurveConfig + Clone,
+    G1::ScalarField: PrimeField,
+    <G1 as CurveConfig>::BaseField: PrimeField,
+{
+    /// Seller: sign the offer for `buyer`, valid until `expiry`
+    pub fn sign<R: Rng>(sk: &SecretKey<G1>, offer: FseOffer<G1>, buyer: &PublicKey<G1>, expiry: u64, rng: &mut R) -> Self {
+        let mut nonce = [0u8; 32];
+        rng.fill(&mut nonce);
+
+        let signature = sign_offer(sk, &offer, buyer, expiry, &nonce, rng);
+        SignedOffer { offer, buyer: buyer.clone(), expiry, nonce, signature }
+    }
+
+    /// Buyer: accept the offer only if it is addressed to `buyer`, signed by the seller,
+    /// not expired and consistent with the messages
+    pub fn verify<C: Clock>(&self, seller_pk: &PublicKey<G1>, buyer: &PublicKey<G1>, message: &[Vec<u8>], clock: &C) -> Result<(), OfferError> {
+        if self.buyer.pk != buyer.pk {
+            return Err(OfferError::WrongBuyer);
+        }
+
+        if !verify_offer_signature(seller_pk, &self.offer.digest(), &self.buyer, self.expiry, &self.nonce, &self.signature) {
+            return Err(OfferError::InvalidSignature);
+        }
+        if clock.now() >= self.expiry {
+            return Err(OfferError::Expired);
+        }
+        if !self.offer.verify(seller_pk, message) {
+            return Err(OfferError::InvalidOffer);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_bn254::g1::Config;
+    use ark_bn254::Fr;
+    use rand::thread_rng;
+
+    use crate::clock::MockClock;
+    use crate::fse::offer::FseOffer;
+    use crate::fse::protocol::FSE;
+    use crate::fse::signed_offer::{OfferError, SignedOffer};
+    use crate::schnorr_signature::key::{PublicKey, SecretKey};
+
+    #[test]
+    fn test() {
+        let message: Vec<Vec<u8>> = (0u8..4u8).map(|i| [i, 1u8, 2u8, 3u8].to_vec()).collect();
+        let clock = MockClock::new(100);
+
+        let (seller_sk, seller_pk): (SecretKey<Config>, PublicKey<Config>) = FSE::gen_key(&mut thread_rng());
+        let (_, buyer_pk): (SecretKey<Config>, PublicKey<Config>) = FSE::gen_key(&mut thread_rng());
+        let (_, other_pk): (SecretKey<Config>, PublicKey<Config>) = FSE::gen_key(&mut thread_rng());
+
+        let (offer, _) = FseOffer::sign(&seller_sk, &message, &mut thread_rng());
+        let signed = SignedOffer::sign(&seller_sk, offer, &buyer_pk, 160, &mut thread_rng());
+        assert_eq!(signed.verify(&seller_pk, &buyer_pk, &message, &clock), Ok(()));
+
+        // replayed to another buyer, as is or re-targeted
+        assert_eq!(signed.verify(&seller_pk, &other_pk, &message, &clock), Err(OfferError::WrongBuyer));
+        let mut retargeted = signed.clone();
+        retargeted.buyer = other_pk.clone();
+        assert_eq!(retargeted.verify(&seller_pk, &other_pk, &message, &clock), Err(OfferError::InvalidSignature));
+
+        // extended expiry
+        let mut extended = signed.clone();
+        extended.expiry = 1000;
+        assert_eq!(extended.verify(&seller_pk, &buyer_pk, &message, &clock), Err(OfferError::InvalidSignature));
+
+        // the nonce is covered too, and two signings of the same terms use different ones
+        let mut renonced = signed.clone();
+        renonced.nonce[0] ^= 1;
+        assert_eq!(renonced.verify(&seller_pk, &buyer_pk, &message, &clock), Err(OfferError::InvalidSignature));
+        let again = SignedOffer::sign(&seller_sk, signed.offer.clone(), &buyer_pk, 160, &mut thread_rng());
+        assert_ne!(again.nonce, signed.nonce);
+
+        // not signed by this seller
+        assert_eq!(signed.verify(&other_pk, &buyer_pk, &message, &clock), Err(OfferError::InvalidSignature));
+
+        // tampering with the root breaks the signature, tampering below it breaks the offer
+        let mut tampered = signed.clone();
+        tampered.offer.root = [0u8; 32];
+        assert_eq!(tampered.verify(&seller_pk, &buyer_pk, &message, &clock), Err(OfferError::InvalidSignature));
+        let mut tampered = signed.clone();
+        tampered.offer.alpha[0] += Fr::from(1u8);
+        assert_eq!(tampered.verify(&seller_pk, &buyer_pk, &message, &clock), Err(OfferError::InvalidOffer));
+
+        clock.set(160);
+        assert_eq!(signed.verify(&seller_pk, &buyer_pk, &message, &clock), Err(OfferError::Expired));
+    }
+}
@@ -0,0 +1,243 @@
+use std::collections::HashSet;
+use std::ops::{Add, Mul};
+
+use ark_ec::{CurveConfig, Group};
+use ark_ec::short_weierstrass::{Projective, SWCurveConfig};
+use ark_ff::PrimeField;
+use rand::Rng;
+use rayon::iter::{IntoParallelIterator, IntoParallelRefIterator};
+use rayon::iter::ParallelIterator;
+
+use crate::fse::offer::FseOffer;
+use crate::hash::Hash256;
+use crate::merkle::Digest;
+use crate::schnorr_signature::key::PublicKey;
+use crate::schnorr_signature::signature::Signature;
+use crate::schnorr_signature::util::group_element_into_bytes;
+use crate::schnorr_signature::verifier::Verifier;
+
+/// Outcome of checking a random subset of an offer
+#[derive(Clone, Debug)]
+pub struct SpotCheckReport {
+    pub n: usize,
+    pub indices: Vec<usize>,
+    pub valid: bool,
+}
+
+impl SpotCheckReport {
+    /// Upper bound on the probability that an offer with at least `bad` invalid
+    /// entries would have passed this check
+    pub fn soundness(&self, bad: usize) -> f64 {
+        escape_probability(self.n, self.indices.len(), bad)
+    }
+}
+
+/// Probability that t distinct uniform indices out of n all miss `bad` invalid entries
+pub fn escape_probability(n: usize, t: usize, bad: usize) -> f64 {
+    if bad == 0 {
+        return 1.0;
+    }
+    if t + bad > n {
+        return 0.0;
+    }
+
+    (0..t).map(|j| (n - bad - j) as f64 / (n - j) as f64).product()
+}
+
+/// Smallest number of samples such that an offer with a fraction `bad_fraction` of
+/// invalid entries escapes with probability at most `target`
+pub fn samples_for(n: usize, bad_fraction: f64, target: f64) -> usize {
+    let bad = (((n as f64) * bad_fraction).ceil() as usize).min(n);
+    if bad == 0 {
+        return n;
+    }
+
+    // running product of escape_probability(n, t, bad)
+    let mut escape = 1.0;
+    for t in 0..n - bad + 1 {
+        if escape <= target {
+            return t;
+        }
+        escape *= (n - bad - t) as f64 / (n - t) as f64;
+    }
+    n - bad + 1
+}
+
+/// Buyer's random challenge, drawn once the seller has committed to the offer root
+pub fn challenge<R: Rng>(rng: &mut R) -> [u8; 32] {
+    let mut res = [0u8; 32];
+    rng.fill(&mut res);
+    res
+}
+
+/// Seed the sample with the offer digest and the buyer's challenge, so neither side controls it alone
+pub fn seed(offer_digest: &Digest, challenge: &[u8; 32]) -> Digest {
+    let mut bytes = offer_digest.to_vec();
+    bytes.extend_from_slice(challenge);
+    Hash256::digest(&bytes)
+}
+
+/// min(t, n) distinct indices in [0, n) derived from the seed
+pub fn sample_indices(seed: &Digest, n: usize, t: usize) -> Vec<usize> {
+    let t = t.min(n);
+    let mut seen = HashSet::with_capacity(t);
+    let mut indices = Vec::with_capacity(t);
+
+    let mut counter = 0u64;
+    while indices.len() < t {
+        let mut bytes = seed.to_vec();
+        bytes.extend(counter.to_le_bytes());
+        let digest = Hash256::digest(&bytes);
+        let index = (u64::from_le_bytes(digest[..8].try_into().unwrap()) % n as u64) as usize;
+        if seen.insert(index) {
+            indices.push(index);
+        }
+        counter += 1;
+    }
+
+    indices
+}
+
+/// Check t random entries of the offer instead of all n of them
+pub fn spot_check<G1>(pk: &PublicKey<G1>, offer: &FseOffer<G1>, message: &[Vec<u8>], challenge: &[u8; 32], t: usize) -> SpotCheckReport
+where
+    G1: SWCurveConfig + Clone,
+    G1::ScalarField: PrimeField,
+    <G1 as CurveConfig>::BaseField: PrimeField,
+{
+    let n = offer.len();
+    let indices = sample_indices(&seed(&offer.digest(), challenge), n, t);
+
+    // the root must bind the offer for the sample to be unpredictable to the seller, and every
+    // sampled entry must exist in each of the offer's vectors
+    if offer.r_g.len() != n || !offer.verify_root(message) || indices.iter().any(|i| *i >= n) {
+        return SpotCheckReport { n, indices, valid: false };
+    }
+
+    let valid = indices.par_iter().map(|i| {
+        let c_i: G1::ScalarField = {
+            let mut bytes = group_element_into_bytes::<G1>(&offer.r_g[*i]);
+            bytes.extend_from_slice(&message[*i]);
+            Hash256::hash_bytes(bytes.as_slice())
+        };
+
+        let com_i = offer.r_g[*i].add(pk.pk.mul(c_i));
+        Projective::generator().mul(G1::ScalarField::from(2u128) * offer.alpha[*i]) == offer.com_k.add(com_i)
+    }).reduce(|| true, |acc, x| acc && x);
+
+    SpotCheckReport { n, indices, valid }
+}
+
+/// Fully verify the recovered signatures, returns the indices that fail
+pub fn audit<G1>(pk: &PublicKey<G1>, message: &[Vec<u8>], signatures: &[Signature<G1>]) -> Vec<usize>
+where
+    G1: SWCurveConfig + Clone,
+    G1::ScalarField: PrimeField,
+    <G1 as CurveConfig>::BaseField: PrimeField,
+{
+    let verifier = Verifier::new(pk.clone());
+
+    (0..message.len()).into_par_iter()
+        .filter(|i| signatures.get(*i).is_none_or(|sig| !verifier.verify(&message[*i], sig)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_bn254::g1::Config;
+    use ark_bn254::Fr;
+    use ark_ec::short_weierstrass::Projective;
+    use ark_ff::Zero;
+    use rand::thread_rng;
+
+    use crate::fse::error::FseError;
+    use crate::fse::offer::FseOffer;
+    use crate::fse::protocol::FSE;
+    use crate::fse::spot_check::{audit, challenge, escape_probability, sample_indices, samples_for, spot_check};
+    use crate::schnorr_signature::key::{PublicKey, SecretKey};
+    use crate::schnorr_signature::signature::Signature;
+
+    #[test]
+    fn test_sample_indices() {
+        let seed = [7u8; 32];
+        let indices = sample_indices(&seed, 100, 20);
+        assert_eq!(indices, sample_indices(&seed, 100, 20));
+        assert_eq!(indices.len(), 20);
+        assert!(indices.iter().all(|i| *i < 100));
+
+        let mut all = sample_indices(&seed, 10, 50);
+        all.sort();
+        assert_eq!(all, (0..10).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_soundness() {
+        assert_eq!(escape_probability(100, 10, 0), 1.0);
+        assert_eq!(escape_probability(100, 91, 10), 0.0);
+        assert!((escape_probability(100, 1, 10) - 0.9).abs() < 1e-12);
+
+        // 1% fraud in 10^7 entries is caught with probability 1 - 2^-40 after a few thousand samples
+        let t = samples_for(10_000_000, 0.01, 2f64.powi(-40));
+        assert!(t > 2_000 && t < 3_000);
+        assert!(escape_probability(10_000_000, t, 100_000) <= 2f64.powi(-40));
+    }
+
+    #[test]
+    fn test() {
+        let message: Vec<Vec<u8>> = (0u8..64u8).map(|i| [i, 1u8, 2u8, 3u8].to_vec()).collect();
+        let (sk, pk): (SecretKey<Config>, PublicKey<Config>) = FSE::gen_key(&mut thread_rng());
+
+        let (offer, k) = FseOffer::sign(&sk, &message, &mut thread_rng());
+        let report = spot_check(&pk, &offer, &message, &challenge(&mut thread_rng()), 16);
+        assert!(report.valid);
+        assert_eq!(report.indices.len(), 16);
+        assert!(report.soundness(32) < 1e-5);
+
+        let signatures = FSE::recover(&pk, &message, &offer.alpha, &offer.r_g, &offer.com_k, k).unwrap();
+        assert!(audit(&pk, &message, &signatures).is_empty());
+    }
+
+    #[test]
+    fn test_fraud() {
+        let message: Vec<Vec<u8>> = (0u8..64u8).map(|i| [i, 1u8, 2u8, 3u8].to_vec()).collect();
+        let (sk, pk): (SecretKey<Config>, PublicKey<Config>) = FSE::gen_key(&mut thread_rng());
+
+        // a seller corrupting every entry is caught by any sample
+        let (mut offer, _) = FseOffer::sign(&sk, &message, &mut thread_rng());
+        offer.alpha.iter_mut().for_each(|alpha| *alpha += Fr::from(1u8));
+        let offer = FseOffer::new(&message, offer.alpha, offer.r_g, offer.com_k);
+        assert!(!spot_check(&pk, &offer, &message, &challenge(&mut thread_rng()), 1).valid);
+
+        // a seller corrupting a single entry may slip through, but recovery names it
+        let (mut offer, k) = FseOffer::sign(&sk, &message, &mut thread_rng());
+        offer.alpha[5] += Fr::from(1u8);
+        let offer = FseOffer::new(&message, offer.alpha, offer.r_g, offer.com_k);
+        assert_eq!(
+            FSE::recover(&pk, &message, &offer.alpha, &offer.r_g, &offer.com_k, k).err(),
+            Some(FseError::InvalidSignatures(vec![5]))
+        );
+
+        // so does the audit of signatures unmasked without checking
+        let signatures: Vec<Signature<Config>> = offer.alpha.iter().zip(&offer.r_g)
+            .map(|(alpha, r_g)| Signature { r_g: *r_g, s: Fr::from(2u8) * alpha - k })
+            .collect();
+        assert_eq!(audit(&pk, &message, &signatures), vec![5]);
+
+        // as is a nonce at infinity, under a root that commits to it
+        let (mut offer, _) = FseOffer::sign(&sk, &message, &mut thread_rng());
+        offer.r_g.iter_mut().for_each(|r_g| *r_g = Projective::zero());
+        let offer = FseOffer::new(&message, offer.alpha, offer.r_g, Projective::zero());
+        assert!(!spot_check(&pk, &offer, &message, &challenge(&mut thread_rng()), 1).valid);
+
+        // an offer that does not match its root is rejected outright
+        let (mut offer, _) = FseOffer::sign(&sk, &message, &mut thread_rng());
+        offer.root = [0u8; 32];
+        assert!(!spot_check(&pk, &offer, &message, &challenge(&mut thread_rng()), 1).valid);
+
+        // as is an offer with fewer nonces than masked scalars, even under a matching root
+        let (mut offer, _) = FseOffer::sign(&sk, &message, &mut thread_rng());
+        offer.r_g.truncate(32);
+        let offer = FseOffer::new(&message, offer.alpha, offer.r_g, offer.com_k);
+        assert!(!spot_check(&pk, &offer, &message, &challenge(&mut thread_rng()), 64).valid);
+    }
+}
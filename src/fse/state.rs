@@ -0,0 +1,515 @@
+use std::fmt;
+use std::ops::Mul;
+
+use ark_ec::{CurveConfig, Group};
+use ark_ec::short_weierstrass::{Projective, SWCurveConfig};
+use ark_ff::PrimeField;
+
+use crate::clock::Clock;
+use crate::fse::error::FseError;
+use crate::fse::protocol::FSE;
+use crate::schnorr_signature::key::PublicKey;
+use crate::schnorr_signature::signature::Signature;
+
+/// Lifecycle of an FSE exchange, Recovered, Aborted and Refunded are terminal
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FseState {
+    Offered,
+    Verified,
+    Paid,
+    Revealed,
+    Recovered,
+    /// The exchange stopped before any payment was made
+    Aborted,
+    /// The payment timed out without a valid reveal and went back to the buyer
+    Refunded,
+}
+
+impl FseState {
+    pub fn is_terminal(&self) -> bool {
+        matches!(self, FseState::Recovered | FseState::Aborted | FseState::Refunded)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TransitionError {
+    /// The event is not allowed in the current state
+    OutOfOrder { state: FseState },
+    /// The revealed k does not open com_k
+    InvalidKey,
+    /// k opened com_k but the unmasked signatures did not verify
+    Recovery(FseError),
+}
+
+impl fmt::Display for TransitionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TransitionError::OutOfOrder { state } => write!(f, "event not allowed in state {:?}", state),
+            TransitionError::InvalidKey => write!(f, "revealed key does not open com_k"),
+            TransitionError::Recovery(err) => write!(f, "recovery failed: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for TransitionError {}
+
+/// Durations, in clock seconds, after which a stalled exchange is given up
+#[derive(Clone, Copy, Debug)]
+pub struct FseTimeouts {
+    /// From the offer until the payment is locked, otherwise Aborted
+    pub payment: u64,
+    /// From the payment until k is revealed, otherwise Refunded
+    pub reveal: u64,
+}
+
+pub enum SellerEvent {
+    /// The buyer accepted the offer after verifying it
+    OfferAccepted,
+    /// The buyer rejected the offer
+    OfferRejected,
+    /// The buyer's payment is locked against com_k
+    PaymentLocked,
+    /// The buyer confirmed that the signatures were recovered
+    RecoveryConfirmed,
+    /// Give up before any payment
+    Abort,
+}
+
+pub enum BuyerEvent<G1>
+where
+    G1: SWCurveConfig + Clone,
+    G1::ScalarField: PrimeField,
+{
+    /// The payment is locked against com_k
+    PaymentLocked,
+    /// The seller published k
+    KeyRevealed(G1::ScalarField),
+    /// Give up before any payment
+    Abort,
+}
+
+/// Move to the timeout state if the deadline of the current state has passed
+fn expire(state: FseState, deadline: u64, now: u64) -> FseState {
+    if now < deadline {
+        return state;
+    }
+
+    match state {
+        FseState::Offered | FseState::Verified => FseState::Aborted,
+        FseState::Paid => FseState::Refunded,
+        _ => state,
+    }
+}
+
+pub struct SellerMachine<G1, C>
+where
+    G1: SWCurveConfig + Clone,
+    G1::ScalarField: PrimeField,
+    C: Clock,
+{
+    state: FseState,
+    deadline: u64,
+    pub timeouts: FseTimeouts,
+    k: G1::ScalarField,
+    clock: C,
+}
+
+impl<G1, C> SellerMachine<G1, C>
+where
+    G1: SWCurveConfig + Clone,
+    G1::ScalarField: PrimeField,
+    C: Clock,
+{
+    /// Start in Offered once the offer masked with k has been sent
+    pub fn new(k: G1::ScalarField, timeouts: FseTimeouts, clock: C) -> Self {
+        let deadline = clock.now().saturating_add(timeouts.payment);
+        SellerMachine {
+            state: FseState::Offered,
+            deadline,
+            timeouts,
+            k,
+            clock,
+        }
+    }
+
+    /// The state as of the last transition, without applying pending timeouts
+    pub fn state(&self) -> FseState {
+        self.state
+    }
+
+    /// Time at which the current state times out
+    pub fn deadline(&self) -> u64 {
+        self.deadline
+    }
+
+    /// Apply any pending timeout and return the current state
+    pub fn tick(&mut self) -> FseState {
+        self.state = expire(self.state, self.deadline, self.clock.now());
+        self.state
+    }
+
+    pub fn handle(&mut self, event: SellerEvent) -> Result<FseState, TransitionError> {
+        let state = self.tick();
+
+        self.state = match (state, event) {
+            (FseState::Offered, SellerEvent::OfferAccepted) => FseState::Verified,
+            (FseState::Offered, SellerEvent::OfferRejected) => FseState::Aborted,
+            (FseState::Offered | FseState::Verified, SellerEvent::Abort) => FseState::Aborted,
+            (FseState::Verified, SellerEvent::PaymentLocked) => {
+                self.deadline = self.clock.now().saturating_add(self.timeouts.reveal);
+                FseState::Paid
+            }
+            (FseState::Revealed, SellerEvent::RecoveryConfirmed) => FseState::Recovered,
+            _ => return Err(TransitionError::OutOfOrder { state }),
+        };
+
+        Ok(self.state)
+    }
+
+    /// Release k to claim the locked payment
+    pub fn reveal(&mut self) -> Result<G1::ScalarField, TransitionError> {
+        let state = self.tick();
+        if state != FseState::Paid {
+            return Err(TransitionError::OutOfOrder { state });
+        }
+
+        self.state = FseState::Revealed;
+        Ok(self.k)
+    }
+}
+
+pub struct BuyerMachine<G1, C>
+where
+    G1: SWCurveConfig + Clone,
+    G1::ScalarField: PrimeField,
+    C: Clock,
+{
+    state: FseState,
+    deadline: u64,
+    pub timeouts: FseTimeouts,
+    pk: PublicKey<G1>,
+    message: Vec<Vec<u8>>,
+    alpha: Vec<G1::ScalarField>,
+    r_g: Vec<Projective<G1>>,
+    com_k: Projective<G1>,
+    k: Option<G1::ScalarField>,
+    clock: C,
+}
+
+impl<G1, C> BuyerMachine<G1, C>
+where
+    G1: SWCurveConfig + Clone,
+    G1::ScalarField: PrimeField,
+    C: Clock,
+{
+    /// Start in Offered on receipt of the seller's offer
+    pub fn new(pk: PublicKey<G1>,
+               message: Vec<Vec<u8>>,
+               alpha: Vec<G1::ScalarField>,
+               r_g: Vec<Projective<G1>>,
+               com_k: Projective<G1>,
+               timeouts: FseTimeouts,
+               clock: C,
+    ) -> Self {
+        let deadline = clock.now().saturating_add(timeouts.payment);
+        BuyerMachine {
+            state: FseState::Offered,
+            deadline,
+            timeouts,
+            pk,
+            message,
+            alpha,
+            r_g,
+            com_k,
+            k: None,
+            clock,
+        }
+    }
+
+    pub fn pk(&self) -> &PublicKey<G1> {
+        &self.pk
+    }
+
+    pub fn message(&self) -> &[Vec<u8>] {
+        &self.message
+    }
+
+    pub fn alpha(&self) -> &[G1::ScalarField] {
+        &self.alpha
+    }
+
+    pub fn r_g(&self) -> &[Projective<G1>] {
+        &self.r_g
+    }
+
+    pub fn com_k(&self) -> &Projective<G1> {
+        &self.com_k
+    }
+
+    /// The state as of the last transition, without applying pending timeouts
+    pub fn state(&self) -> FseState {
+        self.state
+    }
+
+    /// Time at which the current state times out
+    pub fn deadline(&self) -> u64 {
+        self.deadline
+    }
+
+    /// Apply any pending timeout and return the current state
+    pub fn tick(&mut self) -> FseState {
+        self.state = expire(self.state, self.deadline, self.clock.now());
+        self.state
+    }
+
+    /// Check the offer, Verified if it is well formed and Aborted otherwise
+    pub fn verify(&mut self) -> Result<FseState, TransitionError>
+    where
+        <G1 as CurveConfig>::BaseField: PrimeField,
+    {
+        let state = self.tick();
+        if state != FseState::Offered {
+            return Err(TransitionError::OutOfOrder { state });
+        }
+
+        self.state = if FSE::verify(&self.pk, &self.message, &self.alpha, &self.r_g, &self.com_k) {
+            FseState::Verified
+        } else {
+            FseState::Aborted
+        };
+
+        Ok(self.state)
+    }
+
+    pub fn handle(&mut self, event: BuyerEvent<G1>) -> Result<FseState, TransitionError> {
+        let state = self.tick();
+
+        self.state = match (state, event) {
+            (FseState::Offered | FseState::Verified, BuyerEvent::Abort) => FseState::Aborted,
+            (FseState::Verified, BuyerEvent::PaymentLocked) => {
+                self.deadline = self.clock.now().saturating_add(self.timeouts.reveal);
+                FseState::Paid
+            }
+            (FseState::Paid, BuyerEvent::KeyRevealed(k)) => {
+                // a wrong key leaves the payment locked until the refund
+                if Projective::generator().mul(k) != self.com_k {
+                    return Err(TransitionError::InvalidKey);
+                }
+                self.k = Some(k);
+                FseState::Revealed
+            }
+            _ => return Err(TransitionError::OutOfOrder { state }),
+        };
+
+        Ok(self.state)
+    }
+
+    /// Unmask and check the signatures with the revealed k, a failure leaves the
+    /// machine in Revealed with the bad indices in the error
+    pub fn recover(&mut self) -> Result<Vec<Signature<G1>>, TransitionError>
+    where
+        <G1 as CurveConfig>::BaseField: PrimeField,
+    {
+        let k = match (self.state, self.k) {
+            (FseState::Revealed, Some(k)) => k,
+            (state, _) => return Err(TransitionError::OutOfOrder { state }),
+        };
+
+        let signatures = FSE::recover(&self.pk, &self.message, &self.alpha, &self.r_g, &self.com_k, k)
+            .map_err(TransitionError::Recovery)?;
+        self.state = FseState::Recovered;
+        Ok(signatures)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_bn254::g1::Config;
+    use ark_bn254::Fr;
+    use rand::thread_rng;
+
+    use crate::clock::MockClock;
+    use crate::fse::error::FseError;
+    use crate::fse::protocol::FSE;
+    use crate::fse::state::{BuyerEvent, BuyerMachine, FseState, FseTimeouts, SellerEvent, SellerMachine, TransitionError};
+    use crate::schnorr_signature::key::{PublicKey, SecretKey};
+    use crate::schnorr_signature::verifier::Verifier;
+
+    const TIMEOUTS: FseTimeouts = FseTimeouts { payment: 10, reveal: 20 };
+
+    fn setup(clock: &MockClock) -> (SellerMachine<Config, &MockClock>, BuyerMachine<Config, &MockClock>) {
+        let message = vec![
+            [0u8, 1u8, 2u8, 3u8].to_vec(),
+            [4u8, 5u8, 6u8, 7u8].to_vec(),
+        ];
+        let (sk, pk): (SecretKey<Config>, PublicKey<Config>) = FSE::gen_key(&mut thread_rng());
+        let (vec_alpha, r_g, com_k, k) = FSE::sign(&sk, &message, &mut thread_rng());
+
+        let seller = SellerMachine::new(k, TIMEOUTS, clock);
+        let buyer = BuyerMachine::new(pk, message, vec_alpha, r_g, com_k, TIMEOUTS, clock);
+        (seller, buyer)
+    }
+
+    #[test]
+    fn test_honest_exchange() {
+        let clock = MockClock::new(0);
+        let (mut seller, mut buyer) = setup(&clock);
+
+        assert_eq!(buyer.verify(), Ok(FseState::Verified));
+        assert_eq!(seller.handle(SellerEvent::OfferAccepted), Ok(FseState::Verified));
+
+        clock.advance(5);
+        assert_eq!(buyer.handle(BuyerEvent::PaymentLocked), Ok(FseState::Paid));
+        assert_eq!(seller.handle(SellerEvent::PaymentLocked), Ok(FseState::Paid));
+
+        clock.advance(15);
+        let k = seller.reveal().unwrap();
+        assert_eq!(buyer.handle(BuyerEvent::KeyRevealed(k)), Ok(FseState::Revealed));
+
+        let signatures = buyer.recover().unwrap();
+        let verifier = Verifier::new(buyer.pk().clone());
+        for (i, sig) in signatures.iter().enumerate() {
+            assert!(verifier.verify(&buyer.message()[i], sig));
+        }
+        assert_eq!(buyer.state(), FseState::Recovered);
+        assert_eq!(seller.handle(SellerEvent::RecoveryConfirmed), Ok(FseState::Recovered));
+
+        // terminal states do not time out or accept anything
+        clock.advance(100);
+        assert_eq!(buyer.tick(), FseState::Recovered);
+        assert_eq!(buyer.handle(BuyerEvent::Abort), Err(TransitionError::OutOfOrder { state: FseState::Recovered }));
+    }
+
+    #[test]
+    fn test_out_of_order() {
+        let clock = MockClock::new(0);
+        let (mut seller, mut buyer) = setup(&clock);
+
+        // nothing can happen before the offer is verified
+        assert_eq!(buyer.handle(BuyerEvent::PaymentLocked), Err(TransitionError::OutOfOrder { state: FseState::Offered }));
+        assert_eq!(buyer.recover().err(), Some(TransitionError::OutOfOrder { state: FseState::Offered }));
+        assert_eq!(seller.handle(SellerEvent::PaymentLocked), Err(TransitionError::OutOfOrder { state: FseState::Offered }));
+        assert_eq!(seller.reveal(), Err(TransitionError::OutOfOrder { state: FseState::Offered }));
+
+        // k is only accepted once paid, and only revealed once paid
+        buyer.verify().unwrap();
+        seller.handle(SellerEvent::OfferAccepted).unwrap();
+        assert_eq!(buyer.verify(), Err(TransitionError::OutOfOrder { state: FseState::Verified }));
+        assert_eq!(buyer.handle(BuyerEvent::KeyRevealed(Fr::from(1u8))), Err(TransitionError::OutOfOrder { state: FseState::Verified }));
+        assert_eq!(seller.reveal(), Err(TransitionError::OutOfOrder { state: FseState::Verified }));
+
+        // no abort once the payment is locked
+        buyer.handle(BuyerEvent::PaymentLocked).unwrap();
+        seller.handle(SellerEvent::PaymentLocked).unwrap();
+        assert_eq!(buyer.handle(BuyerEvent::Abort), Err(TransitionError::OutOfOrder { state: FseState::Paid }));
+        assert_eq!(seller.handle(SellerEvent::Abort), Err(TransitionError::OutOfOrder { state: FseState::Paid }));
+        assert_eq!(seller.handle(SellerEvent::RecoveryConfirmed), Err(TransitionError::OutOfOrder { state: FseState::Paid }));
+    }
+
+    #[test]
+    fn test_abort_on_invalid_offer() {
+        let clock = MockClock::new(0);
+        let (mut seller, mut buyer) = setup(&clock);
+
+        buyer.alpha[0] += Fr::from(1u8);
+        assert_eq!(buyer.verify(), Ok(FseState::Aborted));
+        assert_eq!(seller.handle(SellerEvent::OfferRejected), Ok(FseState::Aborted));
+        assert_eq!(buyer.handle(BuyerEvent::PaymentLocked), Err(TransitionError::OutOfOrder { state: FseState::Aborted }));
+    }
+
+    #[test]
+    fn test_explicit_abort() {
+        let clock = MockClock::new(0);
+
+        // while offered
+        let (mut seller, mut buyer) = setup(&clock);
+        assert_eq!(buyer.handle(BuyerEvent::Abort), Ok(FseState::Aborted));
+        assert_eq!(seller.handle(SellerEvent::Abort), Ok(FseState::Aborted));
+
+        // once verified
+        let (mut seller, mut buyer) = setup(&clock);
+        buyer.verify().unwrap();
+        seller.handle(SellerEvent::OfferAccepted).unwrap();
+        assert_eq!(buyer.handle(BuyerEvent::Abort), Ok(FseState::Aborted));
+        assert_eq!(seller.handle(SellerEvent::Abort), Ok(FseState::Aborted));
+    }
+
+    #[test]
+    fn test_timeout_before_payment() {
+        // while offered
+        let clock = MockClock::new(0);
+        let (mut seller, mut buyer) = setup(&clock);
+        clock.advance(TIMEOUTS.payment);
+        assert_eq!(buyer.verify(), Err(TransitionError::OutOfOrder { state: FseState::Aborted }));
+        assert_eq!(seller.tick(), FseState::Aborted);
+
+        // once verified
+        let clock = MockClock::new(0);
+        let (mut seller, mut buyer) = setup(&clock);
+        buyer.verify().unwrap();
+        seller.handle(SellerEvent::OfferAccepted).unwrap();
+        clock.advance(TIMEOUTS.payment);
+        assert_eq!(buyer.handle(BuyerEvent::PaymentLocked), Err(TransitionError::OutOfOrder { state: FseState::Aborted }));
+        assert_eq!(seller.handle(SellerEvent::PaymentLocked), Err(TransitionError::OutOfOrder { state: FseState::Aborted }));
+    }
+
+    #[test]
+    fn test_unbounded_timeouts() {
+        // deadlines saturate at the end of the clock instead of wrapping into the past
+        let timeouts = FseTimeouts { payment: u64::MAX, reveal: u64::MAX };
+        let message = vec![[0u8, 1u8, 2u8, 3u8].to_vec()];
+        let (sk, pk): (SecretKey<Config>, PublicKey<Config>) = FSE::gen_key(&mut thread_rng());
+        let (vec_alpha, r_g, com_k, k) = FSE::sign(&sk, &message, &mut thread_rng());
+
+        let clock = MockClock::new(1_000);
+        let mut seller: SellerMachine<Config, _> = SellerMachine::new(k, timeouts, &clock);
+        let mut buyer = BuyerMachine::new(pk, message, vec_alpha, r_g, com_k, timeouts, &clock);
+        assert_eq!(seller.deadline(), u64::MAX);
+
+        assert_eq!(buyer.verify(), Ok(FseState::Verified));
+        assert_eq!(seller.handle(SellerEvent::OfferAccepted), Ok(FseState::Verified));
+        clock.advance(1_000_000);
+        assert_eq!(buyer.handle(BuyerEvent::PaymentLocked), Ok(FseState::Paid));
+        assert_eq!(seller.handle(SellerEvent::PaymentLocked), Ok(FseState::Paid));
+        assert_eq!(buyer.deadline(), u64::MAX);
+
+        assert_eq!(buyer.handle(BuyerEvent::KeyRevealed(seller.reveal().unwrap())), Ok(FseState::Revealed));
+        assert!(buyer.recover().is_ok());
+    }
+
+    #[test]
+    fn test_refund_after_payment() {
+        let clock = MockClock::new(0);
+        let (mut seller, mut buyer) = setup(&clock);
+        buyer.verify().unwrap();
+        seller.handle(SellerEvent::OfferAccepted).unwrap();
+        buyer.handle(BuyerEvent::PaymentLocked).unwrap();
+        seller.handle(SellerEvent::PaymentLocked).unwrap();
+
+        // a wrong key is rejected and the payment stays locked
+        let k = Fr::from(1u8);
+        assert_eq!(buyer.handle(BuyerEvent::KeyRevealed(k)), Err(TransitionError::InvalidKey));
+        assert_eq!(buyer.state(), FseState::Paid);
+
+        // the reveal deadline passes, both sides end up refunded
+        clock.advance(TIMEOUTS.reveal);
+        assert_eq!(buyer.tick(), FseState::Refunded);
+        assert_eq!(seller.reveal(), Err(TransitionError::OutOfOrder { state: FseState::Refunded }));
+    }
+
+    #[test]
+    fn test_recovery_failure() {
+        let clock = MockClock::new(0);
+        let (mut seller, mut buyer) = setup(&clock);
+        buyer.verify().unwrap();
+        seller.handle(SellerEvent::OfferAccepted).unwrap();
+        buyer.handle(BuyerEvent::PaymentLocked).unwrap();
+        seller.handle(SellerEvent::PaymentLocked).unwrap();
+        buyer.handle(BuyerEvent::KeyRevealed(seller.reveal().unwrap())).unwrap();
+
+        // the stored offer no longer unmasks to a valid signature at index 1
+        buyer.alpha[1] += Fr::from(1u8);
+        assert_eq!(buyer.recover().err(), Some(TransitionError::Recovery(FseError::InvalidSignatures(vec![1]))));
+        assert_eq!(buyer.state(), FseState::Revealed);
+    }
+}
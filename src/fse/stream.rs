@@ -0,0 +1,217 @@
+use std::ops::Mul;
+
+use ark_ec::{CurveConfig, Group};
+use ark_ec::short_weierstrass::{Projective, SWCurveConfig};
+use ark_ff::PrimeField;
+use rand::Rng;
+
+use crate::fse::error::FseError;
+use crate::fse::protocol::FSE;
+use crate::hash::Hash256;
+use crate::schnorr_signature::key::{PublicKey, SecretKey};
+use crate::schnorr_signature::signature::Signature;
+
+/// Reverse hash chain of period keys, k_{t-1} = H(k_t), so that k_t opens
+/// every period up to t and none after it
+#[derive(Clone, Debug)]
+pub struct KeyChain<F: PrimeField> {
+    /// keys[t] = k_t
+    pub keys: Vec<F>,
+}
+
+impl<F: PrimeField> KeyChain<F> {
+    pub fn new<R: Rng>(periods: usize, rng: &mut R) -> Self {
+        Self::from_seed(F::rand(rng), periods)
+    }
+
+    /// The seed is the key of the last period
+    pub fn from_seed(seed: F, periods: usize) -> Self {
+        let mut keys = Vec::with_capacity(periods);
+        if periods > 0 {
+            keys.push(seed);
+            for _ in 1..periods {
+                let previous = Hash256::hash_field(keys.last().unwrap());
+                keys.push(previous);
+            }
+            keys.reverse();
+        }
+
+        KeyChain { keys }
+    }
+
+    pub fn periods(&self) -> usize {
+        self.keys.len()
+    }
+
+    pub fn key(&self, t: usize) -> Result<F, FseError> {
+        self.keys.get(t).copied().ok_or(FseError::PeriodOutOfRange(t))
+    }
+
+    /// Walk the chain down from k_t, returns [k_0, ..., k_t]. t comes from the other party,
+    /// so it is checked against the agreed number of periods before anything is allocated
+    pub fn derive(k_t: F, t: usize, periods: usize) -> Result<Vec<F>, FseError> {
+        if t >= periods {
+            return Err(FseError::PeriodOutOfRange(t));
+        }
+
+        let mut keys = Vec::with_capacity(t + 1);
+        keys.push(k_t);
+        for _ in 0..t {
+            let previous = Hash256::hash_field(keys.last().unwrap());
+            keys.push(previous);
+        }
+        keys.reverse();
+        Ok(keys)
+    }
+}
+
+/// Offer for a single period, masked with that period's chain key
+pub struct PeriodOffer<G1>
+where
+    G1: SWCurveConfig + Clone,
+    G1::ScalarField: PrimeField,
+{
+    pub period: usize,
+    pub alpha: Vec<G1::ScalarField>,
+    pub r_g: Vec<Projective<G1>>,
+    /// com_k_t = g^{k_t}
+    pub com_k: Projective<G1>,
+}
+
+/// Streaming FSE: the seller emits one offer per period under a reverse hash chain of keys
+pub struct StreamFSE<G1>
+where
+    G1: SWCurveConfig + Clone,
+    G1::ScalarField: PrimeField,
+{
+    pub sk: SecretKey<G1>,
+    pub chain: KeyChain<G1::ScalarField>,
+}
+
+impl<G1> StreamFSE<G1>
+where
+    G1: SWCurveConfig + Clone,
+    G1::ScalarField: PrimeField,
+{
+    pub fn new<R: Rng>(sk: SecretKey<G1>, periods: usize, rng: &mut R) -> Self {
+        StreamFSE {
+            sk,
+            chain: KeyChain::new(periods, rng),
+        }
+    }
+
+    /// Seller: offer the messages of period t
+    pub fn offer<R: Rng>(&self, t: usize, message: &[Vec<u8>], rng: &mut R) -> Result<PeriodOffer<G1>, FseError>
+    where
+        <G1 as CurveConfig>::BaseField: PrimeField,
+    {
+        let (alpha, r_g, com_k) = FSE::sign_with_key(&self.sk, message, self.chain.key(t)?, rng);
+        Ok(PeriodOffer { period: t, alpha, r_g, com_k })
+    }
+
+    /// Seller: key to reveal once periods up to t have been paid for
+    pub fn reveal(&self, t: usize) -> Result<G1::ScalarField, FseError> {
+        self.chain.key(t)
+    }
+
+    /// Buyer: check a period offer before paying for it
+    pub fn verify_offer(pk: &PublicKey<G1>, message: &[Vec<u8>], offer: &PeriodOffer<G1>) -> bool
+    where
+        <G1 as CurveConfig>::BaseField: PrimeField,
+    {
+        FSE::verify(pk, message, &offer.alpha, &offer.r_g, &offer.com_k)
+    }
+
+    /// Buyer: check k_t against the commitment of its period
+    pub fn verify_key(offer: &PeriodOffer<G1>, k: &G1::ScalarField) -> bool {
+        Projective::generator().mul(*k) == offer.com_k
+    }
+
+    /// Buyer: recover every offered period up to t from the newest revealed key k_t,
+    /// periods after t stay None. message[j] are the messages of offers[j], and periods is
+    /// the length of the stream agreed with the seller
+    #[allow(clippy::type_complexity)]
+    pub fn recover(pk: &PublicKey<G1>,
+                   message: &[Vec<Vec<u8>>],
+                   offers: &[PeriodOffer<G1>],
+                   periods: usize,
+                   t: usize,
+                   k_t: G1::ScalarField,
+    ) -> Result<Vec<Option<Result<Vec<Signature<G1>>, FseError>>>, FseError>
+    where
+        <G1 as CurveConfig>::BaseField: PrimeField,
+    {
+        if message.len() != offers.len() {
+            return Err(FseError::LengthMismatch);
+        }
+        let keys = KeyChain::derive(k_t, t, periods)?;
+
+        Ok(offers.iter().zip(message).map(|(offer, message)| {
+            keys.get(offer.period).map(|k| FSE::recover(pk, message, &offer.alpha, &offer.r_g, &offer.com_k, *k))
+        }).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_bn254::g1::Config;
+    use ark_bn254::Fr;
+    use rand::thread_rng;
+
+    use crate::fse::error::FseError;
+    use crate::fse::protocol::FSE;
+    use crate::fse::stream::{KeyChain, StreamFSE};
+    use crate::schnorr_signature::key::{PublicKey, SecretKey};
+    use crate::schnorr_signature::verifier::Verifier;
+
+    #[test]
+    fn test_key_chain() {
+        let chain: KeyChain<Fr> = KeyChain::new(5, &mut thread_rng());
+        assert_eq!(chain.periods(), 5);
+        assert_eq!(KeyChain::derive(chain.key(3).unwrap(), 3, 5), Ok(chain.keys[..4].to_vec()));
+        assert_eq!(KeyChain::derive(chain.key(4).unwrap(), 4, 5), Ok(chain.keys.clone()));
+
+        // periods past the end of the chain are errors rather than panics or huge allocations
+        assert_eq!(chain.key(5), Err(FseError::PeriodOutOfRange(5)));
+        assert_eq!(KeyChain::derive(chain.key(4).unwrap(), usize::MAX, 5), Err(FseError::PeriodOutOfRange(usize::MAX)));
+    }
+
+    #[test]
+    fn test() {
+        let (sk, pk): (SecretKey<Config>, PublicKey<Config>) = FSE::gen_key(&mut thread_rng());
+        let seller = StreamFSE::new(sk, 4, &mut thread_rng());
+
+        let messages: Vec<Vec<Vec<u8>>> = (0u8..4u8).map(|t| vec![[t, 0u8].to_vec(), [t, 1u8].to_vec()]).collect();
+        let offers: Vec<_> = messages.iter().enumerate().map(|(t, message)| seller.offer(t, message, &mut thread_rng()).unwrap()).collect();
+        for (offer, message) in offers.iter().zip(&messages) {
+            assert!(StreamFSE::verify_offer(&pk, message, offer));
+        }
+
+        // revealing k_2 opens periods 0, 1 and 2 but not 3
+        let k_2 = seller.reveal(2).unwrap();
+        assert!(StreamFSE::verify_key(&offers[2], &k_2));
+        assert!(!StreamFSE::verify_key(&offers[3], &k_2));
+
+        let recovered = StreamFSE::recover(&pk, &messages, &offers, 4, 2, k_2).unwrap();
+        assert!(recovered[3].is_none());
+
+        let verifier = Verifier::new(pk.clone());
+        for t in 0..3 {
+            for (i, sig) in recovered[t].as_ref().unwrap().as_ref().unwrap().iter().enumerate() {
+                assert!(verifier.verify(&messages[t][i], sig));
+            }
+        }
+
+        // claiming k_2 is the key of period 3 does not open anything
+        let recovered = StreamFSE::recover(&pk, &messages, &offers, 4, 3, k_2).unwrap();
+        assert!(recovered.iter().all(|r| matches!(r, Some(Err(FseError::InvalidKey)))));
+
+        // nor does a period the stream never had
+        assert!(seller.offer(4, &messages[0], &mut thread_rng()).is_err());
+        assert_eq!(seller.reveal(4).err(), Some(FseError::PeriodOutOfRange(4)));
+        assert!(matches!(StreamFSE::recover(&pk, &messages, &offers, 4, 4, k_2), Err(FseError::PeriodOutOfRange(4))));
+
+        // every offer needs its messages, trailing periods are not dropped silently
+        assert!(matches!(StreamFSE::recover(&pk, &messages[..3], &offers, 4, 2, k_2), Err(FseError::LengthMismatch)));
+    }
+}
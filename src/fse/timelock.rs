@@ -0,0 +1,363 @@
+use std::ops::{Mul, Sub};
+
+use ark_ec::{CurveConfig, Group};
+use ark_ec::short_weierstrass::{Projective, SWCurveConfig};
+use ark_ff::{BigInteger, PrimeField};
+use ark_std::UniformRand;
+use num_bigint::{BigUint, RandBigInt};
+use rand::Rng;
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+
+use crate::hash::Hash256;
+use crate::schnorr_signature::util::{group_element_into_bytes, scalar_into_bytes};
+
+/// Number of cut-and-choose rounds, a seller locking anything but k gets caught
+/// except with probability 2^-REPETITIONS
+pub const REPETITIONS: usize = 128;
+
+const TIMELOCK_DOMAIN: &[u8] = b"FSE time-lock";
+
+/// Public parameters of the RSW puzzles: N = pq, T sequential squarings and h = g^{2^T} mod N.
+/// Whoever runs the setup learns the factorization and can open puzzles early,
+/// so it must be run by a party the buyer trusts, never by the seller
+#[derive(Clone, Debug)]
+pub struct TimeLockParams {
+    pub modulus: BigUint,
+    pub t: u64,
+    pub g: BigUint,
+    pub h: BigUint,
+}
+
+fn is_probable_prime<R: Rng>(n: &BigUint, rng: &mut R) -> bool {
+    let one = BigUint::from(1u8);
+    let two = BigUint::from(2u8);
+    if *n < two {
+        return false;
+    }
+    for p in [2u8, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37] {
+        if *n == BigUint::from(p) {
+            return true;
+        }
+        if (n % p) == BigUint::from(0u8) {
+            return false;
+        }
+    }
+
+    // Miller-Rabin with n - 1 = d * 2^s
+    let n_1 = n - &one;
+    let s = n_1.trailing_zeros().unwrap_or(0);
+    let d = &n_1 >> s;
+    (0..32).all(|_| {
+        let a = rng.gen_biguint_range(&two, &n_1);
+        let mut x = a.modpow(&d, n);
+        if x == one || x == n_1 {
+            return true;
+        }
+        for _ in 1..s {
+            x = x.modpow(&two, n);
+            if x == n_1 {
+                return true;
+            }
+        }
+        false
+    })
+}
+
+fn random_prime<R: Rng>(bits: u64, rng: &mut R) -> BigUint {
+    loop {
+        let mut candidate = rng.gen_biguint(bits);
+        candidate.set_bit(bits - 1, true);
+        candidate.set_bit(0, true);
+        if is_probable_prime(&candidate, rng) {
+            return candidate;
+        }
+    }
+}
+
+impl TimeLockParams {
+    /// Generate an RSA modulus of `bits` bits and h = g^{2^T} with the trapdoor, then drop the trapdoor
+    pub fn setup<R: Rng>(bits: u64, t: u64, rng: &mut R) -> Self {
+        let one = BigUint::from(1u8);
+        let (p, q) = loop {
+            let p = random_prime(bits / 2, rng);
+            let q = random_prime(bits - bits / 2, rng);
+            if p != q {
+                break (p, q);
+            }
+        };
+        let modulus = &p * &q;
+        let phi = (&p - &one) * (&q - &one);
+
+        let x = rng.gen_biguint_below(&modulus);
+        let g = x.modpow(&BigUint::from(2u8), &modulus);
+        let exponent = BigUint::from(2u8).modpow(&BigUint::from(t), &phi);
+        let h = g.modpow(&exponent, &modulus);
+
+        TimeLockParams { modulus, t, g, h }
+    }
+}
+
+/// Linearly homomorphic RSW puzzle of s: (g^r mod N, h^{rN} (1 + N)^s mod N^2)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Puzzle {
+    pub u: BigUint,
+    pub v: BigUint,
+}
+
+impl Puzzle {
+    pub fn new(params: &TimeLockParams, s: &BigUint, r: &BigUint) -> Self {
+        let n = &params.modulus;
+        let n2 = n * n;
+        let u = params.g.modpow(r, n);
+        let v = params.h.modpow(&(r * n), &n2) * (BigUint::from(1u8) + n).modpow(s, &n2) % &n2;
+        Puzzle { u, v }
+    }
+
+    /// Recover s with T sequential squarings, `on_squaring` is called after each of them.
+    /// None if the puzzle is malformed and does not open to anything
+    pub fn solve<P: FnMut(u64)>(&self, params: &TimeLockParams, mut on_squaring: P) -> Option<BigUint> {
+        let n = &params.modulus;
+        let n2 = n * n;
+
+        let mut w = self.u.clone();
+        for i in 0..params.t {
+            w = &w * &w % n;
+            on_squaring(i + 1);
+        }
+
+        // v / w^N = (1 + N)^s = 1 + sN mod N^2
+        let mask = w.modpow(n, &n2).modinv(&n2)?;
+        let x = &self.v * mask % &n2;
+        if x == BigUint::from(0u8) || (&x - 1u8) % n != BigUint::from(0u8) {
+            return None;
+        }
+        Some((x - 1u8) / n)
+    }
+}
+
+/// One cut-and-choose round: k = a + b with A = g^a, a and b locked in separate puzzles
+#[derive(Clone, Debug)]
+pub struct TimeLockRound<G1>
+where
+    G1: SWCurveConfig + Clone,
+    G1::ScalarField: PrimeField,
+{
+    pub a_g: Projective<G1>,
+    pub z_a: Puzzle,
+    pub z_b: Puzzle,
+}
+
+/// Plaintext and puzzle randomness of the half opened in a round
+#[derive(Clone, Debug)]
+pub struct PuzzleOpening<F: PrimeField> {
+    pub value: F,
+    pub randomness: BigUint,
+}
+
+/// Where the solver is: `squarings` out of `total` done on the puzzle of `round`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SolveProgress {
+    pub round: usize,
+    pub squarings: u64,
+    pub total: u64,
+}
+
+/// k locked in time-lock puzzles, with a non-interactive proof that they solve to the dlog of com_k
+#[derive(Clone, Debug)]
+pub struct TimeLockedKey<G1>
+where
+    G1: SWCurveConfig + Clone,
+    G1::ScalarField: PrimeField,
+{
+    pub rounds: Vec<TimeLockRound<G1>>,
+    pub openings: Vec<PuzzleOpening<G1::ScalarField>>,
+}
+
+fn to_biguint<F: PrimeField>(f: &F) -> BigUint {
+    BigUint::from_bytes_le(&scalar_into_bytes(f))
+}
+
+fn from_biguint<F: PrimeField>(n: &BigUint) -> F {
+    F::from_le_bytes_mod_order(&n.to_bytes_le())
+}
+
+impl<G1> TimeLockedKey<G1>
+where
+    G1: SWCurveConfig + Clone,
+    G1::ScalarField: PrimeField,
+    <G1 as CurveConfig>::BaseField: PrimeField,
+{
+    /// Fiat-Shamir challenge: bit i selects which half of round i gets opened
+    fn challenge(params: &TimeLockParams, com_k: &Projective<G1>, label: &[u8], rounds: &[TimeLockRound<G1>]) -> Vec<bool> {
+        let mut bytes = TIMELOCK_DOMAIN.to_vec();
+        let mut absorb = |chunk: &[u8]| {
+            bytes.extend((chunk.len() as u64).to_le_bytes());
+            bytes.extend_from_slice(chunk);
+        };
+        absorb(label);
+        absorb(&params.modulus.to_bytes_le());
+        absorb(&params.t.to_le_bytes());
+        absorb(&params.g.to_bytes_le());
+        absorb(&params.h.to_bytes_le());
+        absorb(&group_element_into_bytes::<G1>(com_k));
+        for round in rounds {
+            absorb(&group_element_into_bytes::<G1>(&round.a_g));
+            for z in [&round.z_a, &round.z_b] {
+                absorb(&z.u.to_bytes_le());
+                absorb(&z.v.to_bytes_le());
+            }
+        }
+
+        let digest = Hash256::digest(&bytes);
+        (0..rounds.len()).map(|i| (digest[i / 8] >> (i % 8)) & 1 == 1).collect()
+    }
+
+    /// Seller: lock k so that anyone can recover it after T squarings, `label` binds it to one trade.
+    /// Panics if the modulus does not exceed the scalar field
+    pub fn new<R: Rng>(params: &TimeLockParams, k: G1::ScalarField, label: &[u8], rng: &mut R) -> Self {
+        assert!(params.modulus > BigUint::from_bytes_le(&G1::ScalarField::MODULUS.to_bytes_le()), "time-lock modulus is too small");
+        let com_k = Projective::generator().mul(k);
+        let n2 = &params.modulus * &params.modulus;
+
+        let secrets: Vec<(G1::ScalarField, BigUint, BigUint)> = (0..REPETITIONS)
+            .map(|_| (G1::ScalarField::rand(rng), rng.gen_biguint_below(&n2), rng.gen_biguint_below(&n2)))
+            .collect();
+        let rounds: Vec<TimeLockRound<G1>> = secrets.iter().map(|(a, r_a, r_b)| TimeLockRound {
+            a_g: Projective::generator().mul(*a),
+            z_a: Puzzle::new(params, &to_biguint(a), r_a),
+            z_b: Puzzle::new(params, &to_biguint(&(k - a)), r_b),
+        }).collect();
+
+        let openings = Self::challenge(params, &com_k, label, &rounds).into_iter().zip(secrets)
+            .map(|(bit, (a, r_a, r_b))| match bit {
+                false => PuzzleOpening { value: a, randomness: r_a },
+                true => PuzzleOpening { value: k - a, randomness: r_b },
+            })
+            .collect();
+
+        TimeLockedKey { rounds, openings }
+    }
+
+    /// Buyer: check, without solving anything, that the puzzles lock the dlog of com_k
+    pub fn verify(&self, params: &TimeLockParams, com_k: &Projective<G1>, label: &[u8]) -> bool {
+        if self.rounds.len() != REPETITIONS || self.openings.len() != REPETITIONS {
+            return false;
+        }
+
+        let bits = Self::challenge(params, com_k, label, &self.rounds);
+        (0..REPETITIONS).into_par_iter().all(|i| {
+            let round = &self.rounds[i];
+            let opening = &self.openings[i];
+            let (puzzle, committed) = match bits[i] {
+                false => (&round.z_a, round.a_g),
+                true => (&round.z_b, com_k.sub(round.a_g)),
+            };
+
+            Puzzle::new(params, &to_biguint(&opening.value), &opening.randomness) == *puzzle
+                && Projective::generator().mul(opening.value) == committed
+        })
+    }
+
+    /// Buyer: solve the unopened puzzle of each round in turn until one yields k, moving on
+    /// past malformed puzzles, and report progress about every percent of a puzzle
+    pub fn solve<P: FnMut(SolveProgress)>(&self, params: &TimeLockParams, com_k: &Projective<G1>, label: &[u8], mut progress: P) -> Option<G1::ScalarField> {
+        let bits = Self::challenge(params, com_k, label, &self.rounds);
+        let step = (params.t / 100).max(1);
+        let total = params.t;
+
+        for (round, (opening, bit)) in self.openings.iter().zip(bits).enumerate() {
+            let locked = match bit {
+                false => &self.rounds[round].z_b,
+                true => &self.rounds[round].z_a,
+            };
+
+            let Some(solved) = locked.solve(params, |squarings| {
+                if squarings % step == 0 || squarings == total {
+                    progress(SolveProgress { round, squarings, total });
+                }
+            }) else {
+                continue;
+            };
+            let k = opening.value + from_biguint::<G1::ScalarField>(&solved);
+            if Projective::generator().mul(k) == *com_k {
+                return Some(k);
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_bn254::g1::Config;
+    use ark_bn254::Fr;
+    use num_bigint::BigUint;
+    use rand::thread_rng;
+
+    use crate::fse::offer::FseOffer;
+    use crate::fse::protocol::FSE;
+    use crate::fse::timelock::{Puzzle, SolveProgress, TimeLockedKey, TimeLockParams};
+    use crate::schnorr_signature::key::{PublicKey, SecretKey};
+
+    #[test]
+    fn test_puzzle() {
+        let params = TimeLockParams::setup(384, 100, &mut thread_rng());
+        let s = BigUint::from(123456789u64);
+        let puzzle = Puzzle::new(&params, &s, &BigUint::from(987654321u64));
+
+        let mut squarings = 0;
+        assert_eq!(puzzle.solve(&params, |i| squarings = i), Some(s));
+        assert_eq!(squarings, 100);
+
+        // a base with no inverse and a v that cancels to zero are refused, not a panic
+        assert_eq!(Puzzle { u: BigUint::from(0u8), v: puzzle.v.clone() }.solve(&params, |_| ()), None);
+        assert_eq!(Puzzle { u: puzzle.u.clone(), v: BigUint::from(0u8) }.solve(&params, |_| ()), None);
+    }
+
+    #[test]
+    fn test() {
+        let message: Vec<Vec<u8>> = (0u8..3u8).map(|i| [i, 1u8, 2u8, 3u8].to_vec()).collect();
+        let (sk, pk): (SecretKey<Config>, PublicKey<Config>) = FSE::gen_key(&mut thread_rng());
+        let params = TimeLockParams::setup(384, 200, &mut thread_rng());
+
+        let (offer, k) = FseOffer::sign(&sk, &message, &mut thread_rng());
+        let label = offer.digest();
+        let locked = TimeLockedKey::new(&params, k, &label, &mut thread_rng());
+        assert!(locked.verify(&params, &offer.com_k, &label));
+        assert!(!locked.verify(&params, &offer.com_k, b"another offer"));
+
+        // the seller vanished, the buyer grinds through the puzzle
+        let mut reports: Vec<SolveProgress> = vec![];
+        let solved = locked.solve(&params, &offer.com_k, &label, |p| reports.push(p)).unwrap();
+        assert_eq!(solved, k);
+        assert_eq!(reports.len(), 100);
+        assert_eq!(reports.last(), Some(&SolveProgress { round: 0, squarings: 200, total: 200 }));
+
+        let signatures = FSE::recover(&pk, &message, &offer.alpha, &offer.r_g, &offer.com_k, solved).unwrap();
+        assert_eq!(signatures.len(), message.len());
+    }
+
+    #[test]
+    fn test_bad_lock() {
+        let (sk, _): (SecretKey<Config>, PublicKey<Config>) = FSE::gen_key(&mut thread_rng());
+        let params = TimeLockParams::setup(384, 10, &mut thread_rng());
+        let (offer, k) = FseOffer::sign(&sk, &[[0u8].to_vec()], &mut thread_rng());
+        let label = offer.digest();
+
+        // puzzles of some other key
+        let locked = TimeLockedKey::new(&params, k + Fr::from(1u8), &label, &mut thread_rng());
+        assert!(!locked.verify(&params, &offer.com_k, &label));
+        assert_eq!(locked.solve(&params, &offer.com_k, &label, |_| ()), None);
+
+        // a garbled puzzle
+        let mut locked = TimeLockedKey::new(&params, k, &label, &mut thread_rng());
+        locked.rounds[0].z_a.v += 1u8;
+        locked.rounds[0].z_b.v += 1u8;
+        assert!(!locked.verify(&params, &offer.com_k, &label));
+
+        // a round that does not open at all is skipped, the next one still yields k
+        locked.rounds[0].z_a.v = BigUint::from(0u8);
+        locked.rounds[0].z_b.v = BigUint::from(0u8);
+        assert_eq!(locked.solve(&params, &offer.com_k, &label, |_| ()), Some(k));
+    }
+}
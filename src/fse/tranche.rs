@@ -0,0 +1,203 @@
+use std::marker::PhantomData;
+use std::ops::{Add, Mul, Range};
+
+use ark_ec::{CurveConfig, Group};
+use ark_ec::short_weierstrass::{Projective, SWCurveConfig};
+use ark_ff::PrimeField;
+use rand::Rng;
+use rayon::iter::IntoParallelIterator;
+use rayon::iter::ParallelIterator;
+
+use crate::fse::error::FseError;
+use crate::fse::protocol::FSE;
+use crate::hash::Hash256;
+use crate::schnorr_signature::key::{PublicKey, SecretKey};
+use crate::schnorr_signature::signature::Signature;
+use crate::schnorr_signature::util::group_element_into_bytes;
+
+/// FSE over a message set partitioned into consecutive tranches of the given sizes,
+/// tranche j is masked with its own k_j so it can be paid for and unlocked on its own
+pub struct TrancheFSE<G1>
+where
+    G1: SWCurveConfig + Clone,
+    G1::ScalarField: PrimeField,
+{
+    phantom: PhantomData<G1>,
+}
+
+/// Index ranges of the tranches, None if the sizes do not cover exactly n messages
+pub fn tranche_ranges(sizes: &[usize], n: usize) -> Option<Vec<Range<usize>>> {
+    let mut start: usize = 0;
+    let mut ranges = Vec::with_capacity(sizes.len());
+    for size in sizes {
+        let end = start.checked_add(*size)?;
+        ranges.push(start..end);
+        start = end;
+    }
+
+    (start == n).then_some(ranges)
+}
+
+impl<G1> TrancheFSE<G1>
+where
+    G1: SWCurveConfig + Clone,
+    G1::ScalarField: PrimeField,
+{
+    /// Fails with LengthMismatch if the tranche sizes do not add up to the number of messages
+    #[allow(clippy::type_complexity)]
+    pub fn sign<R: Rng>(sk: &SecretKey<G1>, message: &[Vec<u8>], sizes: &[usize], rng: &mut R) -> Result<(Vec<G1::ScalarField>, Vec<Projective<G1>>, Vec<Projective<G1>>, Vec<G1::ScalarField>), FseError>
+    where
+        <G1 as CurveConfig>::BaseField: PrimeField,
+    {
+        let ranges = tranche_ranges(sizes, message.len()).ok_or(FseError::LengthMismatch)?;
+
+        let mut vec_alpha = Vec::with_capacity(message.len());
+        let mut vec_r_g = Vec::with_capacity(message.len());
+        let mut vec_com_k = Vec::with_capacity(ranges.len());
+        let mut vec_k = Vec::with_capacity(ranges.len());
+
+        for range in ranges {
+            let (alpha, r_g, com_k, k) = FSE::sign(sk, &message[range], rng);
+            vec_alpha.extend(alpha);
+            vec_r_g.extend(r_g);
+            vec_com_k.push(com_k);
+            vec_k.push(k);
+        }
+
+        Ok((vec_alpha, vec_r_g, vec_com_k, vec_k))
+    }
+
+    /// Check every index against the commitment of its tranche in a single pass
+    pub fn verify(pk: &PublicKey<G1>,
+                  message: &[Vec<u8>],
+                  alpha: &[G1::ScalarField],
+                  r_g: &[Projective<G1>],
+                  sizes: &[usize],
+                  com_k: &[Projective<G1>],
+    ) -> bool
+    where
+        <G1 as CurveConfig>::BaseField: PrimeField,
+    {
+        let n = message.len();
+        if alpha.len() != n || r_g.len() != n || sizes.len() != com_k.len() || tranche_ranges(sizes, n).is_none() {
+            return false;
+        }
+
+        // tranche index of every message
+        let tranche: Vec<usize> = sizes.iter().enumerate().flat_map(|(j, size)| std::iter::repeat_n(j, *size)).collect();
+
+        (0..n).into_par_iter().map(|i| {
+            let c_i: G1::ScalarField = {
+                let mut bytes = group_element_into_bytes::<G1>(&r_g[i]);
+                bytes.extend_from_slice(&message[i]);
+                Hash256::hash_bytes(bytes.as_slice())
+            };
+
+            let com_i = r_g[i].add(pk.pk.mul(c_i));
+            Projective::generator().mul(G1::ScalarField::from(2u128) * alpha[i]) == com_k[tranche[i]].add(com_i)
+        }).reduce(|| true, |acc, x| acc && x)
+    }
+
+    /// Recover the tranches whose key has been revealed, the others stay None,
+    /// bad indices in an error are indices into the whole message set.
+    /// Fails with LengthMismatch unless the sizes add up to the number of messages and the
+    /// offer, the commitments and the keys all have the matching length
+    #[allow(clippy::type_complexity)]
+    pub fn recover(pk: &PublicKey<G1>,
+                   message: &[Vec<u8>],
+                   alpha: &[G1::ScalarField],
+                   r_g: &[Projective<G1>],
+                   sizes: &[usize],
+                   com_k: &[Projective<G1>],
+                   k: &[Option<G1::ScalarField>],
+    ) -> Result<Vec<Option<Result<Vec<Signature<G1>>, FseError>>>, FseError>
+    where
+        <G1 as CurveConfig>::BaseField: PrimeField,
+    {
+        let ranges = tranche_ranges(sizes, message.len()).ok_or(FseError::LengthMismatch)?;
+        if alpha.len() != message.len() || r_g.len() != message.len() || com_k.len() != sizes.len() || k.len() != sizes.len() {
+            return Err(FseError::LengthMismatch);
+        }
+
+        Ok(ranges.into_iter().zip(com_k.iter().zip(k)).map(|(range, (com_k_j, k_j))| {
+            k_j.map(|k_j| {
+                let start = range.start;
+                FSE::recover(pk, &message[range.clone()], &alpha[range.clone()], &r_g[range], com_k_j, k_j)
+                    .map_err(|err| match err {
+                        FseError::InvalidSignatures(bad) => FseError::InvalidSignatures(bad.into_iter().map(|i| i + start).collect()),
+                        err => err,
+                    })
+            })
+        }).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_bn254::g1::Config;
+    use ark_bn254::Fr;
+    use rand::thread_rng;
+
+    use crate::fse::error::FseError;
+    use crate::fse::protocol::FSE;
+    use crate::fse::tranche::{tranche_ranges, TrancheFSE};
+    use crate::schnorr_signature::key::{PublicKey, SecretKey};
+    use crate::schnorr_signature::verifier::Verifier;
+
+    #[test]
+    fn test() {
+        let message: Vec<Vec<u8>> = (0u8..6u8).map(|i| [i, 1u8, 2u8, 3u8].to_vec()).collect();
+        let sizes = [1, 3, 2];
+        let (sk, pk): (SecretKey<Config>, PublicKey<Config>) = FSE::gen_key(&mut thread_rng());
+
+        let (vec_alpha, r_g, com_k, k) = TrancheFSE::sign(&sk, &message, &sizes, &mut thread_rng()).unwrap();
+        assert_eq!(com_k.len(), 3);
+        assert!(TrancheFSE::verify(&pk, &message, &vec_alpha, &r_g, &sizes, &com_k));
+
+        // tranches are bound to their own commitment
+        let swapped = vec![com_k[1], com_k[0], com_k[2]];
+        assert!(!TrancheFSE::verify(&pk, &message, &vec_alpha, &r_g, &sizes, &swapped));
+        assert!(!TrancheFSE::verify(&pk, &message, &vec_alpha, &r_g, &[3, 3], &com_k[..2]));
+
+        // only the second tranche has been paid for
+        let recovered = TrancheFSE::recover(&pk, &message, &vec_alpha, &r_g, &sizes, &com_k, &[None, Some(k[1]), None]).unwrap();
+        assert!(recovered[0].is_none());
+        assert!(recovered[2].is_none());
+
+        let verifier = Verifier::new(pk.clone());
+        for (sig, i) in recovered[1].as_ref().unwrap().as_ref().unwrap().iter().zip(1..4) {
+            assert!(verifier.verify(&message[i], sig));
+        }
+
+        // a key revealed for the wrong tranche is rejected
+        let recovered = TrancheFSE::recover(&pk, &message, &vec_alpha, &r_g, &sizes, &com_k, &[Some(k[1]), None, None]).unwrap();
+        assert!(matches!(recovered[0], Some(Err(FseError::InvalidKey))));
+    }
+
+    #[test]
+    fn test_invalid_tranche() {
+        let message: Vec<Vec<u8>> = (0u8..4u8).map(|i| [i, 1u8, 2u8, 3u8].to_vec()).collect();
+        let sizes = [2, 2];
+        let (sk, pk): (SecretKey<Config>, PublicKey<Config>) = FSE::gen_key(&mut thread_rng());
+
+        let (mut vec_alpha, r_g, com_k, k) = TrancheFSE::sign(&sk, &message, &sizes, &mut thread_rng()).unwrap();
+        vec_alpha[3] += Fr::from(1u8);
+
+        let keys = [Some(k[0]), Some(k[1])];
+        let recovered = TrancheFSE::recover(&pk, &message, &vec_alpha, &r_g, &sizes, &com_k, &keys).unwrap();
+        assert!(matches!(recovered[0], Some(Ok(_))));
+        assert_eq!(recovered[1].as_ref().unwrap().as_ref().err(), Some(&FseError::InvalidSignatures(vec![3])));
+
+        // sizes that do not cover the messages and a key per tranche that is missing are errors
+        assert_eq!(TrancheFSE::sign(&sk, &message, &[2, 1], &mut thread_rng()).err(), Some(FseError::LengthMismatch));
+        assert_eq!(TrancheFSE::recover(&pk, &message, &vec_alpha, &r_g, &[3, 2], &com_k, &keys).err(), Some(FseError::LengthMismatch));
+        assert_eq!(TrancheFSE::recover(&pk, &message, &vec_alpha, &r_g, &sizes, &com_k, &keys[..1]).err(), Some(FseError::LengthMismatch));
+    }
+
+    #[test]
+    fn test_tranche_ranges() {
+        assert_eq!(tranche_ranges(&[2, 0, 3], 5), Some(vec![0..2, 2..2, 2..5]));
+        assert_eq!(tranche_ranges(&[2, 2], 5), None);
+        assert_eq!(tranche_ranges(&[usize::MAX, 2], 1), None);
+    }
+}
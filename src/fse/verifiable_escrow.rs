@@ -0,0 +1,265 @@
+use std::ops::{Mul, Sub};
+
+use ark_ec::{CurveConfig, Group};
+use ark_ec::short_weierstrass::{Projective, SWCurveConfig};
+use ark_ff::PrimeField;
+use ark_std::UniformRand;
+use rand::Rng;
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+
+use crate::hash::Hash256;
+use crate::schnorr_signature::key::{PublicKey, SecretKey};
+use crate::schnorr_signature::util::{group_element_into_bytes, scalar_into_bytes};
+
+/// Number of cut-and-choose rounds, a seller escrowing anything but k gets caught
+/// except with probability 2^-REPETITIONS
+pub const REPETITIONS: usize = 128;
+
+const ESCROW_DOMAIN: &[u8] = b"FSE verifiable escrow";
+
+/// Hashed ElGamal ciphertext of a scalar x: (g^r, x + H(pk^r))
+#[derive(Clone, Debug)]
+pub struct Ciphertext<G1>
+where
+    G1: SWCurveConfig + Clone,
+    G1::ScalarField: PrimeField,
+{
+    pub u: Projective<G1>,
+    pub v: G1::ScalarField,
+}
+
+/// One cut-and-choose round: k = a + b with A = g^a, a and b encrypted separately
+#[derive(Clone, Debug)]
+pub struct EscrowRound<G1>
+where
+    G1: SWCurveConfig + Clone,
+    G1::ScalarField: PrimeField,
+{
+    pub a_g: Projective<G1>,
+    pub c_a: Ciphertext<G1>,
+    pub c_b: Ciphertext<G1>,
+}
+
+/// Plaintext and encryption randomness of the half opened in a round
+#[derive(Clone, Debug)]
+pub struct Opening<F: PrimeField> {
+    pub value: F,
+    pub randomness: F,
+}
+
+/// k encrypted to the arbiter, with a non-interactive proof that it decrypts to the dlog of com_k
+#[derive(Clone, Debug)]
+pub struct KeyEscrowProof<G1>
+where
+    G1: SWCurveConfig + Clone,
+    G1::ScalarField: PrimeField,
+{
+    pub rounds: Vec<EscrowRound<G1>>,
+    pub openings: Vec<Opening<G1::ScalarField>>,
+}
+
+fn mask<G1>(shared: &Projective<G1>) -> G1::ScalarField
+where
+    G1: SWCurveConfig + Clone,
+    G1::ScalarField: PrimeField,
+    <G1 as CurveConfig>::BaseField: PrimeField,
+{
+    Hash256::hash_bytes(group_element_into_bytes::<G1>(shared).as_slice())
+}
+
+fn encrypt<G1>(pk: &PublicKey<G1>, x: G1::ScalarField, r: G1::ScalarField) -> Ciphertext<G1>
+where
+    G1: SWCurveConfig + Clone,
+    G1::ScalarField: PrimeField,
+    <G1 as CurveConfig>::BaseField: PrimeField,
+{
+    Ciphertext { u: Projective::generator().mul(r), v: x + mask(&pk.pk.mul(r)) }
+}
+
+fn decrypt<G1>(sk: &SecretKey<G1>, c: &Ciphertext<G1>) -> G1::ScalarField
+where
+    G1: SWCurveConfig + Clone,
+    G1::ScalarField: PrimeField,
+    <G1 as CurveConfig>::BaseField: PrimeField,
+{
+    c.v - mask(&c.u.mul(sk.sk))
+}
+
+/// Fiat-Shamir challenge: bit i selects which half of round i gets opened
+fn challenge<G1>(pk: &PublicKey<G1>, com_k: &Projective<G1>, label: &[u8], rounds: &[EscrowRound<G1>]) -> Vec<bool>
+where
+    G1: SWCurveConfig + Clone,
+    G1::ScalarField: PrimeField,
+    <G1 as CurveConfig>::BaseField: PrimeField,
+{
+    let mut bytes = ESCROW_DOMAIN.to_vec();
+    bytes.extend((label.len() as u64).to_le_bytes());
+    bytes.extend_from_slice(label);
+    bytes.extend(group_element_into_bytes::<G1>(&pk.pk));
+    bytes.extend(group_element_into_bytes::<G1>(com_k));
+    for round in rounds {
+        bytes.extend(group_element_into_bytes::<G1>(&round.a_g));
+        for c in [&round.c_a, &round.c_b] {
+            bytes.extend(group_element_into_bytes::<G1>(&c.u));
+            bytes.extend(scalar_into_bytes(&c.v));
+        }
+    }
+
+    let digest = Hash256::digest(&bytes);
+    (0..rounds.len()).map(|i| (digest[i / 8] >> (i % 8)) & 1 == 1).collect()
+}
+
+/// Seller: encrypt k to the arbiter, `label` binds the escrow to one trade, e.g. an offer digest
+pub fn escrow_k<G1, R>(arbiter_pk: &PublicKey<G1>, k: G1::ScalarField, label: &[u8], rng: &mut R) -> KeyEscrowProof<G1>
+where
+    G1: SWCurveConfig + Clone,
+    G1::ScalarField: PrimeField,
+    <G1 as CurveConfig>::BaseField: PrimeField,
+    R: Rng,
+{
+    let com_k = Projective::generator().mul(k);
+
+    let secrets: Vec<[G1::ScalarField; 3]> = (0..REPETITIONS)
+        .map(|_| [G1::ScalarField::rand(rng), G1::ScalarField::rand(rng), G1::ScalarField::rand(rng)])
+        .collect();
+    let rounds: Vec<EscrowRound<G1>> = secrets.iter().map(|[a, r_a, r_b]| EscrowRound {
+        a_g: Projective::generator().mul(*a),
+        c_a: encrypt(arbiter_pk, *a, *r_a),
+        c_b: encrypt(arbiter_pk, k - a, *r_b),
+    }).collect();
+
+    let openings = challenge(arbiter_pk, &com_k, label, &rounds).into_iter().zip(secrets)
+        .map(|(bit, [a, r_a, r_b])| match bit {
+            false => Opening { value: a, randomness: r_a },
+            true => Opening { value: k - a, randomness: r_b },
+        })
+        .collect();
+
+    KeyEscrowProof { rounds, openings }
+}
+
+/// Buyer: check that the escrow decrypts, under the arbiter's key, to the dlog of com_k
+pub fn verify_escrow<G1>(arbiter_pk: &PublicKey<G1>, com_k: &Projective<G1>, label: &[u8], escrow: &KeyEscrowProof<G1>) -> bool
+where
+    G1: SWCurveConfig + Clone,
+    G1::ScalarField: PrimeField,
+    <G1 as CurveConfig>::BaseField: PrimeField,
+{
+    if escrow.rounds.len() != REPETITIONS || escrow.openings.len() != REPETITIONS {
+        return false;
+    }
+
+    let bits = challenge(arbiter_pk, com_k, label, &escrow.rounds);
+    (0..REPETITIONS).into_par_iter().all(|i| {
+        let round = &escrow.rounds[i];
+        let opening = &escrow.openings[i];
+        let (ciphertext, committed) = match bits[i] {
+            false => (&round.c_a, round.a_g),
+            true => (&round.c_b, com_k.sub(round.a_g)),
+        };
+
+        let reencrypted = encrypt(arbiter_pk, opening.value, opening.randomness);
+        reencrypted.u == ciphertext.u && reencrypted.v == ciphertext.v
+            && Projective::generator().mul(opening.value) == committed
+    })
+}
+
+/// Arbiter: called on when the seller was paid but did not reveal, decrypts k from
+/// a valid escrow. None if the escrow does not verify or does not open com_k
+pub fn arbiter_resolve<G1>(arbiter_sk: &SecretKey<G1>, com_k: &Projective<G1>, label: &[u8], escrow: &KeyEscrowProof<G1>) -> Option<G1::ScalarField>
+where
+    G1: SWCurveConfig + Clone,
+    G1::ScalarField: PrimeField,
+    <G1 as CurveConfig>::BaseField: PrimeField,
+{
+    let arbiter_pk = PublicKey { pk: Projective::generator().mul(arbiter_sk.sk) };
+    if !verify_escrow(&arbiter_pk, com_k, label, escrow) {
+        return None;
+    }
+
+    // any round whose two halves were both encrypted honestly yields k
+    escrow.rounds.iter()
+        .map(|round| decrypt(arbiter_sk, &round.c_a) + decrypt(arbiter_sk, &round.c_b))
+        .find(|k| Projective::generator().mul(*k) == *com_k)
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_bn254::g1::Config;
+    use ark_bn254::Fr;
+    use rand::thread_rng;
+
+    use crate::fse::offer::FseOffer;
+    use crate::fse::protocol::FSE;
+    use crate::fse::verifiable_escrow::{arbiter_resolve, escrow_k, verify_escrow};
+    use crate::schnorr_signature::key::{PublicKey, SecretKey};
+    use crate::schnorr_signature::verifier::Verifier;
+
+    fn message() -> Vec<Vec<u8>> {
+        (0u8..3u8).map(|i| [i, 1u8, 2u8, 3u8].to_vec()).collect()
+    }
+
+    #[test]
+    fn test_honest() {
+        let message = message();
+        let (sk, pk): (SecretKey<Config>, PublicKey<Config>) = FSE::gen_key(&mut thread_rng());
+        let (_, arbiter_pk): (SecretKey<Config>, PublicKey<Config>) = FSE::gen_key(&mut thread_rng());
+
+        let (offer, k) = FseOffer::sign(&sk, &message, &mut thread_rng());
+        let label = offer.digest();
+        let escrow = escrow_k(&arbiter_pk, k, &label, &mut thread_rng());
+        assert!(offer.verify(&pk, &message));
+        assert!(verify_escrow(&arbiter_pk, &offer.com_k, &label, &escrow));
+
+        // the seller reveals after payment, the arbiter is never contacted
+        let signatures = FSE::recover(&pk, &message, &offer.alpha, &offer.r_g, &offer.com_k, k).unwrap();
+        assert_eq!(signatures.len(), message.len());
+    }
+
+    #[test]
+    fn test_dispute() {
+        let message = message();
+        let (sk, pk): (SecretKey<Config>, PublicKey<Config>) = FSE::gen_key(&mut thread_rng());
+        let (arbiter_sk, arbiter_pk): (SecretKey<Config>, PublicKey<Config>) = FSE::gen_key(&mut thread_rng());
+
+        let (offer, k) = FseOffer::sign(&sk, &message, &mut thread_rng());
+        let label = offer.digest();
+        let escrow = escrow_k(&arbiter_pk, k, &label, &mut thread_rng());
+        assert!(verify_escrow(&arbiter_pk, &offer.com_k, &label, &escrow));
+
+        // the seller goes silent after payment, the arbiter decrypts k for the buyer
+        let resolved = arbiter_resolve(&arbiter_sk, &offer.com_k, &label, &escrow).unwrap();
+        let signatures = FSE::recover(&pk, &message, &offer.alpha, &offer.r_g, &offer.com_k, resolved).unwrap();
+        let verifier = Verifier::new(pk.clone());
+        for (m_i, sig) in message.iter().zip(&signatures) {
+            assert!(verifier.verify(m_i, sig));
+        }
+
+        // another arbiter cannot open it
+        let (other_sk, _): (SecretKey<Config>, PublicKey<Config>) = FSE::gen_key(&mut thread_rng());
+        assert!(arbiter_resolve(&other_sk, &offer.com_k, &label, &escrow).is_none());
+    }
+
+    #[test]
+    fn test_bad_escrow() {
+        let (sk, _): (SecretKey<Config>, PublicKey<Config>) = FSE::gen_key(&mut thread_rng());
+        let (arbiter_sk, arbiter_pk): (SecretKey<Config>, PublicKey<Config>) = FSE::gen_key(&mut thread_rng());
+        let (offer, k) = FseOffer::sign(&sk, &message(), &mut thread_rng());
+        let label = offer.digest();
+
+        // an escrow of some other key
+        let escrow = escrow_k(&arbiter_pk, k + Fr::from(1u8), &label, &mut thread_rng());
+        assert!(!verify_escrow(&arbiter_pk, &offer.com_k, &label, &escrow));
+        assert!(arbiter_resolve(&arbiter_sk, &offer.com_k, &label, &escrow).is_none());
+
+        // an escrow made for another trade
+        let escrow = escrow_k(&arbiter_pk, k, b"another offer", &mut thread_rng());
+        assert!(!verify_escrow(&arbiter_pk, &offer.com_k, &label, &escrow));
+
+        // a garbled ciphertext
+        let mut escrow = escrow_k(&arbiter_pk, k, &label, &mut thread_rng());
+        escrow.rounds[0].c_a.v += Fr::from(1u8);
+        escrow.rounds[0].c_b.v += Fr::from(1u8);
+        assert!(!verify_escrow(&arbiter_pk, &offer.com_k, &label, &escrow));
+    }
+}
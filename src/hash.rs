@@ -1,14 +1,48 @@
 use ark_crypto_primitives::crh::CRHScheme;
 use ark_crypto_primitives::crh::sha256::Sha256;
-use ark_ff::{PrimeField};
+use ark_ec::AffineRepr;
+use ark_ec::short_weierstrass::{Affine, Projective, SWCurveConfig};
+use ark_ff::{BigInteger, PrimeField};
 
 pub struct Hash256;
 
 impl Hash256 {
     /// Hash bytes element and convert the result into a new field element
     pub fn hash_bytes<F: PrimeField>(bytes: &[u8]) -> F {
+        F::from_le_bytes_mod_order(&Self::digest(bytes))
+    }
+
+    /// Raw 32-byte SHA-256 digest
+    pub fn digest(bytes: &[u8]) -> [u8; 32] {
         let hash_bytes = Sha256::evaluate(&(), bytes).unwrap();
-        F::from_le_bytes_mod_order(&hash_bytes)
+        let mut res = [0u8; 32];
+        res.copy_from_slice(&hash_bytes);
+        res
+    }
+
+    /// Hash a field element into a new field element of the same field
+    pub fn hash_field<F: PrimeField>(f: &F) -> F {
+        Self::hash_bytes(&f.into_bigint().to_bytes_le())
+    }
+
+    /// Hash bytes onto the curve by try-and-increment, so nobody knows the discrete log of the result
+    pub fn hash_to_curve<G1>(bytes: &[u8]) -> Projective<G1>
+    where
+        G1: SWCurveConfig,
+        G1::BaseField: PrimeField,
+    {
+        for counter in 0u64.. {
+            let mut input = bytes.to_vec();
+            input.extend(counter.to_le_bytes());
+            let x = G1::BaseField::from_le_bytes_mod_order(&Self::digest(&input));
+            if let Some(point) = Affine::<G1>::get_point_from_x_unchecked(x, false) {
+                let point = point.clear_cofactor();
+                if !point.is_zero() {
+                    return point.into_group();
+                }
+            }
+        }
+        unreachable!()
     }
 }
 
@@ -16,9 +50,26 @@ impl Hash256 {
 mod tests {
     use crate::hash::Hash256;
     use ark_bn254::Fr;
+    use ark_bn254::g1::Config;
+    use ark_ec::CurveGroup;
 
     #[test]
     fn test_hash() {
         println!("{}", Hash256::hash_bytes::<Fr>(&[0u8, 0u8, 0u8, 0u8]));
     }
+
+    #[test]
+    fn test_hash_field() {
+        let f = Fr::from(7u8);
+        assert_eq!(Hash256::hash_field(&f), Hash256::hash_field(&Fr::from(7u8)));
+        assert_ne!(Hash256::hash_field(&f), f);
+    }
+
+    #[test]
+    fn test_hash_to_curve() {
+        let z = Hash256::hash_to_curve::<Config>(b"info");
+        assert!(z.into_affine().is_on_curve());
+        assert_eq!(z, Hash256::hash_to_curve::<Config>(b"info"));
+        assert_ne!(z, Hash256::hash_to_curve::<Config>(b"other info"));
+    }
 }
\ No newline at end of file
@@ -0,0 +1,170 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use ark_ec::short_weierstrass::{Projective, SWCurveConfig};
+use ark_ff::PrimeField;
+
+use crate::clock::Clock;
+use crate::ledger::contract::{ContractId, ContractState, KeyRevealContract};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LedgerError {
+    UnknownAccount(String),
+    AccountExists(String),
+    InsufficientFunds,
+    UnknownContract(ContractId),
+    /// The contract was already claimed, refunded or settled through the escrow
+    NotLocked,
+    /// Claim attempted at or after the deadline
+    Expired,
+    /// Refund attempted before the deadline
+    NotExpired,
+    /// g^k does not match com_k
+    InvalidKey,
+    /// The timeout puts the deadline past the end of the clock
+    InvalidTimeout,
+}
+
+impl fmt::Display for LedgerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LedgerError::UnknownAccount(id) => write!(f, "unknown account {}", id),
+            LedgerError::AccountExists(id) => write!(f, "account {} already exists", id),
+            LedgerError::InsufficientFunds => write!(f, "insufficient funds"),
+            LedgerError::UnknownContract(id) => write!(f, "unknown contract {}", id),
+            LedgerError::NotLocked => write!(f, "contract is not locked"),
+            LedgerError::Expired => write!(f, "contract deadline has passed"),
+            LedgerError::NotExpired => write!(f, "contract deadline has not passed"),
+            LedgerError::InvalidKey => write!(f, "key does not open com_k"),
+            LedgerError::InvalidTimeout => write!(f, "timeout is out of range"),
+        }
+    }
+}
+
+impl std::error::Error for LedgerError {}
+
+/// In-memory stand-in for a chain holding balances and key-reveal contracts
+pub struct Ledger<G1, C>
+where
+    G1: SWCurveConfig + Clone,
+    G1::ScalarField: PrimeField,
+    C: Clock,
+{
+    clock: C,
+    accounts: HashMap<String, u64>,
+    contracts: Vec<KeyRevealContract<G1>>,
+}
+
+impl<G1, C> Ledger<G1, C>
+where
+    G1: SWCurveConfig + Clone,
+    G1::ScalarField: PrimeField,
+    C: Clock,
+{
+    pub fn new(clock: C) -> Self {
+        Ledger {
+            clock,
+            accounts: HashMap::new(),
+            contracts: Vec::new(),
+        }
+    }
+
+    pub fn clock(&self) -> &C {
+        &self.clock
+    }
+
+    pub fn open_account(&mut self, id: &str, balance: u64) -> Result<(), LedgerError> {
+        if self.accounts.contains_key(id) {
+            return Err(LedgerError::AccountExists(id.to_string()));
+        }
+        self.accounts.insert(id.to_string(), balance);
+        Ok(())
+    }
+
+    pub fn balance(&self, id: &str) -> Option<u64> {
+        self.accounts.get(id).copied()
+    }
+
+    pub fn contract(&self, id: ContractId) -> Option<&KeyRevealContract<G1>> {
+        self.contracts.get(id)
+    }
+
+    /// Move `amount` from the buyer into a contract that the seller can claim with k until `now + timeout`
+    pub fn lock(&mut self, buyer: &str, seller: &str, amount: u64, com_k: Projective<G1>, timeout: u64) -> Result<ContractId, LedgerError> {
+        if !self.accounts.contains_key(seller) {
+            return Err(LedgerError::UnknownAccount(seller.to_string()));
+        }
+        let balance = self.accounts.get_mut(buyer).ok_or_else(|| LedgerError::UnknownAccount(buyer.to_string()))?;
+        if *balance < amount {
+            return Err(LedgerError::InsufficientFunds);
+        }
+        let deadline = self.clock.now().checked_add(timeout).ok_or(LedgerError::InvalidTimeout)?;
+        *balance -= amount;
+
+        self.contracts.push(KeyRevealContract {
+            buyer: buyer.to_string(),
+            seller: seller.to_string(),
+            amount,
+            com_k,
+            deadline,
+            state: ContractState::Locked,
+        });
+
+        Ok(self.contracts.len() - 1)
+    }
+
+    /// Pay the seller against k, which becomes public on the ledger
+    pub fn claim(&mut self, id: ContractId, k: G1::ScalarField) -> Result<(), LedgerError> {
+        let now = self.clock.now();
+        let contract = self.contracts.get_mut(id).ok_or(LedgerError::UnknownContract(id))?;
+
+        if !matches!(contract.state, ContractState::Locked) {
+            return Err(LedgerError::NotLocked);
+        }
+        if now >= contract.deadline {
+            return Err(LedgerError::Expired);
+        }
+        if !contract.accepts(&k) {
+            return Err(LedgerError::InvalidKey);
+        }
+
+        contract.state = ContractState::Claimed { k };
+        *self.accounts.entry(contract.seller.clone()).or_insert(0) += contract.amount;
+        Ok(())
+    }
+
+    /// Pay the seller once the deadline has passed without a claim, on a ruling that the
+    /// escrow agents hand k to the buyer
+    pub fn settle_by_escrow(&mut self, id: ContractId) -> Result<(), LedgerError> {
+        let now = self.clock.now();
+        let contract = self.contracts.get_mut(id).ok_or(LedgerError::UnknownContract(id))?;
+
+        if !matches!(contract.state, ContractState::Locked) {
+            return Err(LedgerError::NotLocked);
+        }
+        if now < contract.deadline {
+            return Err(LedgerError::NotExpired);
+        }
+
+        contract.state = ContractState::Escrowed;
+        *self.accounts.entry(contract.seller.clone()).or_insert(0) += contract.amount;
+        Ok(())
+    }
+
+    /// Return the funds to the buyer once the deadline has passed without a claim
+    pub fn refund(&mut self, id: ContractId) -> Result<(), LedgerError> {
+        let now = self.clock.now();
+        let contract = self.contracts.get_mut(id).ok_or(LedgerError::UnknownContract(id))?;
+
+        if !matches!(contract.state, ContractState::Locked) {
+            return Err(LedgerError::NotLocked);
+        }
+        if now < contract.deadline {
+            return Err(LedgerError::NotExpired);
+        }
+
+        contract.state = ContractState::Refunded;
+        *self.accounts.entry(contract.buyer.clone()).or_insert(0) += contract.amount;
+        Ok(())
+    }
+}
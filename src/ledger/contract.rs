@@ -0,0 +1,57 @@
+use std::ops::Mul;
+
+use ark_ec::Group;
+use ark_ec::short_weierstrass::{Projective, SWCurveConfig};
+use ark_ff::PrimeField;
+
+pub type ContractId = usize;
+
+#[derive(Clone, Debug)]
+pub enum ContractState<G1>
+where
+    G1: SWCurveConfig + Clone,
+    G1::ScalarField: PrimeField,
+{
+    /// Buyer funds are held by the contract
+    Locked,
+    /// The seller was paid by publishing k
+    Claimed { k: G1::ScalarField },
+    /// The timeout passed and the buyer took the funds back
+    Refunded,
+    /// The timeout passed without a claim and the arbiter paid the seller,
+    /// the buyer gets k from the escrow agents instead
+    Escrowed,
+}
+
+/// Key-reveal contract: pays `amount` to the seller against a k with g^k = com_k,
+/// or back to the buyer once `deadline` has passed
+#[derive(Clone, Debug)]
+pub struct KeyRevealContract<G1>
+where
+    G1: SWCurveConfig + Clone,
+    G1::ScalarField: PrimeField,
+{
+    pub buyer: String,
+    pub seller: String,
+    pub amount: u64,
+    pub com_k: Projective<G1>,
+    pub deadline: u64,
+    pub state: ContractState<G1>,
+}
+
+impl<G1> KeyRevealContract<G1>
+where
+    G1: SWCurveConfig + Clone,
+    G1::ScalarField: PrimeField,
+{
+    pub fn accepts(&self, k: &G1::ScalarField) -> bool {
+        Projective::generator().mul(*k) == self.com_k
+    }
+
+    pub fn revealed_key(&self) -> Option<G1::ScalarField> {
+        match self.state {
+            ContractState::Claimed { k } => Some(k),
+            _ => None,
+        }
+    }
+}
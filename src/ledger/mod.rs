@@ -0,0 +1,4 @@
+pub mod chain;
+pub mod contract;
+pub mod watcher;
+mod test;
\ No newline at end of file
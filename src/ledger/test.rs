@@ -0,0 +1,107 @@
+#[cfg(test)]
+mod tests {
+    use ark_bn254::g1::Config;
+    use ark_bn254::Fr;
+    use rand::thread_rng;
+
+    use crate::clock::MockClock;
+    use crate::fse::error::FseError;
+    use crate::fse::protocol::FSE;
+    use crate::ledger::chain::{Ledger, LedgerError};
+    use crate::ledger::contract::ContractState;
+    use crate::ledger::watcher::{BuyerWatcher, WatchEvent};
+    use crate::schnorr_signature::key::{PublicKey, SecretKey};
+    use crate::schnorr_signature::verifier::Verifier;
+
+    fn setup() -> Ledger<Config, MockClock> {
+        let mut ledger = Ledger::new(MockClock::new(1_000));
+        ledger.open_account("buyer", 100).unwrap();
+        ledger.open_account("seller", 0).unwrap();
+        ledger
+    }
+
+    #[test]
+    fn test_claim_and_recover() {
+        let message = vec![
+            [0u8, 1u8, 2u8, 3u8].to_vec(),
+            [4u8, 5u8, 6u8, 7u8].to_vec(),
+        ];
+        let (sk, pk): (SecretKey<Config>, PublicKey<Config>) = FSE::gen_key(&mut thread_rng());
+        let (vec_alpha, r_g, com_k, k) = FSE::sign(&sk, &message, &mut thread_rng());
+        assert!(FSE::verify(&pk, &message, &vec_alpha, &r_g, &com_k));
+
+        let mut ledger = setup();
+        let id = ledger.lock("buyer", "seller", 40, com_k, 60).unwrap();
+        assert_eq!(ledger.balance("buyer"), Some(60));
+
+        let watcher = BuyerWatcher::new(id, pk.clone(), message.clone(), vec_alpha, r_g);
+        assert!(matches!(watcher.poll(&mut ledger), Ok(WatchEvent::Pending)));
+
+        ledger.clock().advance(30);
+        assert_eq!(ledger.claim(id, k + Fr::from(1u8)), Err(LedgerError::InvalidKey));
+        ledger.claim(id, k).unwrap();
+        assert_eq!(ledger.balance("seller"), Some(40));
+        assert_eq!(ledger.claim(id, k), Err(LedgerError::NotLocked));
+
+        let signatures = match watcher.poll(&mut ledger) {
+            Ok(WatchEvent::Recovered(signatures)) => signatures,
+            _ => panic!("expected the watcher to recover the signatures"),
+        };
+        let verifier = Verifier::new(pk);
+        for (i, sig) in signatures.iter().enumerate() {
+            assert!(verifier.verify(&message[i], sig));
+        }
+    }
+
+    #[test]
+    fn test_refund_after_timeout() {
+        let message = vec![[0u8, 1u8, 2u8, 3u8].to_vec()];
+        let (sk, pk): (SecretKey<Config>, PublicKey<Config>) = FSE::gen_key(&mut thread_rng());
+        let (vec_alpha, r_g, com_k, k) = FSE::sign(&sk, &message, &mut thread_rng());
+
+        let mut ledger = setup();
+        let id = ledger.lock("buyer", "seller", 40, com_k, 60).unwrap();
+        assert_eq!(ledger.refund(id), Err(LedgerError::NotExpired));
+
+        ledger.clock().advance(60);
+        assert_eq!(ledger.claim(id, k), Err(LedgerError::Expired));
+
+        let watcher = BuyerWatcher::new(id, pk, message, vec_alpha, r_g);
+        assert!(matches!(watcher.poll(&mut ledger), Ok(WatchEvent::Refunded)));
+        assert_eq!(ledger.balance("buyer"), Some(100));
+        assert_eq!(ledger.balance("seller"), Some(0));
+        assert!(matches!(ledger.contract(id).unwrap().state, ContractState::Refunded));
+        assert_eq!(ledger.refund(id), Err(LedgerError::NotLocked));
+    }
+
+    #[test]
+    fn test_fraud() {
+        let message = vec![[0u8, 1u8, 2u8, 3u8].to_vec(), [4u8, 5u8, 6u8, 7u8].to_vec()];
+        let (sk, pk): (SecretKey<Config>, PublicKey<Config>) = FSE::gen_key(&mut thread_rng());
+        let (mut vec_alpha, r_g, com_k, k) = FSE::sign(&sk, &message, &mut thread_rng());
+        vec_alpha[1] += Fr::from(1u8);
+
+        let mut ledger = setup();
+        let id = ledger.lock("buyer", "seller", 40, com_k, 60).unwrap();
+        ledger.claim(id, k).unwrap();
+
+        let watcher = BuyerWatcher::new(id, pk, message, vec_alpha, r_g);
+        assert!(matches!(watcher.poll(&mut ledger), Ok(WatchEvent::Fraud(FseError::InvalidSignatures(bad))) if bad == vec![1]));
+    }
+
+    #[test]
+    fn test_lock_errors() {
+        let (sk, _): (SecretKey<Config>, PublicKey<Config>) = FSE::gen_key(&mut thread_rng());
+        let (_, _, com_k, _) = FSE::sign(&sk, &[[0u8].to_vec()], &mut thread_rng());
+
+        let mut ledger = setup();
+        assert_eq!(ledger.lock("buyer", "seller", 101, com_k, 60), Err(LedgerError::InsufficientFunds));
+        assert_eq!(ledger.lock("nobody", "seller", 1, com_k, 60), Err(LedgerError::UnknownAccount("nobody".to_string())));
+        assert_eq!(ledger.open_account("buyer", 1), Err(LedgerError::AccountExists("buyer".to_string())));
+        assert_eq!(ledger.claim(7, Fr::from(1u8)), Err(LedgerError::UnknownContract(7)));
+
+        // a deadline past the end of the clock is refused instead of wrapping into the past
+        assert_eq!(ledger.lock("buyer", "seller", 1, com_k, u64::MAX), Err(LedgerError::InvalidTimeout));
+        assert_eq!(ledger.balance("buyer"), Some(100));
+    }
+}
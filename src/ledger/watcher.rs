@@ -0,0 +1,76 @@
+use ark_ec::CurveConfig;
+use ark_ec::short_weierstrass::{Projective, SWCurveConfig};
+use ark_ff::PrimeField;
+
+use crate::clock::Clock;
+use crate::fse::error::FseError;
+use crate::fse::protocol::FSE;
+use crate::ledger::chain::{Ledger, LedgerError};
+use crate::ledger::contract::{ContractId, ContractState};
+use crate::schnorr_signature::key::PublicKey;
+use crate::schnorr_signature::signature::Signature;
+
+pub enum WatchEvent<G1>
+where
+    G1: SWCurveConfig + Clone,
+    G1::ScalarField: PrimeField,
+{
+    /// Nothing happened on the contract yet
+    Pending,
+    /// The seller claimed the payment, the revealed k recovered the signatures
+    Recovered(Vec<Signature<G1>>),
+    /// The seller claimed the payment but the signatures did not verify
+    Fraud(FseError),
+    /// The deadline passed without a claim and the funds went back to the buyer
+    Refunded,
+    /// The deadline passed without a claim and the arbiter paid the seller,
+    /// k is to be rebuilt from the escrow agents' shares
+    Escrowed,
+}
+
+/// Buyer side of an escrowed FSE purchase: polls the key-reveal contract,
+/// recovers the signatures as soon as k shows up and refunds after the timeout
+pub struct BuyerWatcher<G1>
+where
+    G1: SWCurveConfig + Clone,
+    G1::ScalarField: PrimeField,
+{
+    pub contract: ContractId,
+    pub pk: PublicKey<G1>,
+    pub message: Vec<Vec<u8>>,
+    pub alpha: Vec<G1::ScalarField>,
+    pub r_g: Vec<Projective<G1>>,
+}
+
+impl<G1> BuyerWatcher<G1>
+where
+    G1: SWCurveConfig + Clone,
+    G1::ScalarField: PrimeField,
+{
+    pub fn new(contract: ContractId, pk: PublicKey<G1>, message: Vec<Vec<u8>>, alpha: Vec<G1::ScalarField>, r_g: Vec<Projective<G1>>) -> Self {
+        BuyerWatcher { contract, pk, message, alpha, r_g }
+    }
+
+    pub fn poll<C: Clock>(&self, ledger: &mut Ledger<G1, C>) -> Result<WatchEvent<G1>, LedgerError>
+    where
+        <G1 as CurveConfig>::BaseField: PrimeField,
+    {
+        let contract = ledger.contract(self.contract).ok_or(LedgerError::UnknownContract(self.contract))?;
+
+        match contract.state {
+            ContractState::Claimed { k } => Ok(match FSE::recover(&self.pk, &self.message, &self.alpha, &self.r_g, &contract.com_k, k) {
+                Ok(signatures) => WatchEvent::Recovered(signatures),
+                Err(err) => WatchEvent::Fraud(err),
+            }),
+            ContractState::Refunded => Ok(WatchEvent::Refunded),
+            ContractState::Escrowed => Ok(WatchEvent::Escrowed),
+            ContractState::Locked => {
+                if ledger.clock().now() < contract.deadline {
+                    return Ok(WatchEvent::Pending);
+                }
+                ledger.refund(self.contract)?;
+                Ok(WatchEvent::Refunded)
+            }
+        }
+    }
+}
@@ -6,4 +6,16 @@ pub mod blind_schnorr_signature;
 
 pub mod hash;
 
-pub mod fse;
\ No newline at end of file
+pub mod fse;
+
+pub mod clock;
+
+pub mod ledger;
+
+pub mod merkle;
+
+pub mod clause_blind_schnorr;
+
+pub mod partially_blind_schnorr_signature;
+
+pub mod session_store;
\ No newline at end of file
@@ -1,8 +1,3 @@
-pub mod hash;
-pub mod schnorr_signature;
-pub mod blind_schnorr_signature;
-pub mod blind_fse;
-
 use std::mem;
 
 fn main() {
@@ -0,0 +1,127 @@
+use crate::hash::Hash256;
+
+pub type Digest = [u8; 32];
+
+/// Hash of the padding leaves up to the next power of two
+const EMPTY: Digest = [0u8; 32];
+
+/// Inclusion proof: the sibling of every node on the path from the leaf to the root
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MerkleProof {
+    pub index: usize,
+    pub siblings: Vec<Digest>,
+}
+
+/// Binary SHA-256 Merkle tree over pre-hashed leaves, padded to a power of two
+#[derive(Clone, Debug)]
+pub struct MerkleTree {
+    /// levels[0] are the (padded) leaves, the last level is the root
+    levels: Vec<Vec<Digest>>,
+    len: usize,
+}
+
+pub fn hash_leaf(data: &[u8]) -> Digest {
+    let mut bytes = Vec::with_capacity(data.len() + 1);
+    bytes.push(0u8);
+    bytes.extend_from_slice(data);
+    Hash256::digest(&bytes)
+}
+
+fn hash_node(left: &Digest, right: &Digest) -> Digest {
+    let mut bytes = Vec::with_capacity(65);
+    bytes.push(1u8);
+    bytes.extend_from_slice(left);
+    bytes.extend_from_slice(right);
+    Hash256::digest(&bytes)
+}
+
+impl MerkleTree {
+    pub fn new(leaves: Vec<Digest>) -> Self {
+        let len = leaves.len();
+
+        let mut level = leaves;
+        level.resize(len.next_power_of_two(), EMPTY);
+
+        let mut levels = vec![level];
+        while levels.last().unwrap().len() > 1 {
+            let next = levels.last().unwrap().chunks(2).map(|pair| hash_node(&pair[0], &pair[1])).collect();
+            levels.push(next);
+        }
+
+        MerkleTree { levels, len }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn root(&self) -> Digest {
+        self.levels.last().unwrap()[0]
+    }
+
+    pub fn prove(&self, index: usize) -> Option<MerkleProof> {
+        if index >= self.len {
+            return None;
+        }
+
+        let siblings = self.levels[..self.levels.len() - 1].iter().enumerate()
+            .map(|(depth, level)| level[(index >> depth) ^ 1])
+            .collect();
+
+        Some(MerkleProof { index, siblings })
+    }
+
+    /// Check that `leaf` sits at `proof.index` under `root`
+    pub fn verify(root: &Digest, leaf: &Digest, proof: &MerkleProof) -> bool {
+        if proof.siblings.len() < usize::BITS as usize && proof.index >> proof.siblings.len() != 0 {
+            return false;
+        }
+
+        let computed = proof.siblings.iter().enumerate().fold(*leaf, |node, (depth, sibling)| {
+            if (proof.index >> depth) & 1 == 0 {
+                hash_node(&node, sibling)
+            } else {
+                hash_node(sibling, &node)
+            }
+        });
+
+        computed == *root
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::merkle::{hash_leaf, MerkleTree};
+
+    #[test]
+    fn test_merkle_tree() {
+        for n in [1usize, 2, 3, 5, 8] {
+            let leaves: Vec<_> = (0..n).map(|i| hash_leaf(&[i as u8])).collect();
+            let tree = MerkleTree::new(leaves.clone());
+            assert_eq!(tree.len(), n);
+
+            for (i, leaf) in leaves.iter().enumerate() {
+                let proof = tree.prove(i).unwrap();
+                assert!(MerkleTree::verify(&tree.root(), leaf, &proof));
+                assert!(!MerkleTree::verify(&tree.root(), &hash_leaf(&[0xff]), &proof));
+            }
+            assert!(tree.prove(n).is_none());
+        }
+    }
+
+    #[test]
+    fn test_merkle_proof_wrong_index() {
+        let leaves: Vec<_> = (0..4u8).map(|i| hash_leaf(&[i])).collect();
+        let tree = MerkleTree::new(leaves.clone());
+
+        let mut proof = tree.prove(1).unwrap();
+        proof.index = 2;
+        assert!(!MerkleTree::verify(&tree.root(), &leaves[1], &proof));
+        proof.index = 5;
+        assert!(!MerkleTree::verify(&tree.root(), &leaves[1], &proof));
+    }
+}
@@ -0,0 +1,4 @@
+pub mod signature;
+pub mod signer;
+pub mod verifier;
+mod test;
\ No newline at end of file
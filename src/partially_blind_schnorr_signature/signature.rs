@@ -0,0 +1,45 @@
+use ark_ec::short_weierstrass::{Projective, SWCurveConfig};
+use ark_ff::PrimeField;
+
+use crate::hash::Hash256;
+use crate::schnorr_signature::util::group_element_into_bytes;
+
+/// Abe–Okamoto partially blind signature, a witness indistinguishable proof of knowledge of
+/// either log_g(pk) or log_g(z) where z = F(info)
+#[derive(Clone)]
+pub struct PartiallyBlindSignature<G1>
+where
+    G1: SWCurveConfig + Clone,
+    G1::ScalarField: PrimeField,
+{
+    pub rho: G1::ScalarField,
+    pub omega: G1::ScalarField,
+    pub sigma: G1::ScalarField,
+    pub delta: G1::ScalarField,
+}
+
+/// z = F(info), a group element nobody knows the discrete log of
+pub fn info_element<G1>(info: &[u8]) -> Projective<G1>
+where
+    G1: SWCurveConfig + Clone,
+    G1::ScalarField: PrimeField,
+    G1::BaseField: PrimeField,
+{
+    let mut bytes = b"partially blind Schnorr info".to_vec();
+    bytes.extend_from_slice(info);
+    Hash256::hash_to_curve(&bytes)
+}
+
+/// H(alpha || beta || z || message)
+pub fn challenge<G1>(alpha: &Projective<G1>, beta: &Projective<G1>, z: &Projective<G1>, message: &[u8]) -> G1::ScalarField
+where
+    G1: SWCurveConfig + Clone,
+    G1::ScalarField: PrimeField,
+    G1::BaseField: PrimeField,
+{
+    let mut bytes = group_element_into_bytes::<G1>(alpha);
+    bytes.extend(group_element_into_bytes::<G1>(beta));
+    bytes.extend(group_element_into_bytes::<G1>(z));
+    bytes.extend_from_slice(message);
+    Hash256::hash_bytes(bytes.as_slice())
+}
@@ -0,0 +1,93 @@
+use std::ops::{Add, Mul};
+
+use ark_ec::short_weierstrass::{Projective, SWCurveConfig};
+use ark_ff::PrimeField;
+use ark_std::UniformRand;
+use rand::Rng;
+
+use crate::partially_blind_schnorr_signature::signature::info_element;
+use crate::partially_blind_schnorr_signature::verifier::PBSVerifierFirstRoundMessage;
+use crate::schnorr_signature::key::SecretKey;
+use crate::schnorr_signature::signer::Signer;
+
+/// Partially blind Schnorr signer (Abe, Okamoto 2000): the message stays hidden from the signer,
+/// the public info both parties agreed on does not
+pub struct PBSSigner<G1>
+where
+    G1: SWCurveConfig + Clone,
+    G1::ScalarField: PrimeField,
+{
+    pub sk: SecretKey<G1>,
+    pub g: Projective<G1>,
+}
+
+/// Randomness of one session, consumed by the second round so it cannot answer twice
+pub struct PBSSignerSecretRandomness<G1>
+where
+    G1: SWCurveConfig + Clone,
+    G1::ScalarField: PrimeField,
+{
+    u: G1::ScalarField,
+    s: G1::ScalarField,
+    d: G1::ScalarField,
+}
+
+pub struct PBSSignerFirstRoundMessage<G1>
+where
+    G1: SWCurveConfig + Clone,
+    G1::ScalarField: PrimeField,
+{
+    /// a = g^u
+    pub a: Projective<G1>,
+
+    /// b = g^s * z^d
+    pub b: Projective<G1>,
+}
+
+pub struct PBSSignerSecondRoundMessage<G1>
+where
+    G1: SWCurveConfig + Clone,
+    G1::ScalarField: PrimeField,
+{
+    pub r: G1::ScalarField,
+    pub c: G1::ScalarField,
+    pub s: G1::ScalarField,
+    pub d: G1::ScalarField,
+}
+
+impl<G1> PBSSigner<G1>
+where
+    G1: SWCurveConfig + Clone,
+    G1::ScalarField: PrimeField,
+    G1::BaseField: PrimeField,
+{
+    pub fn new(signer: &Signer<G1>) -> Self {
+        PBSSigner {
+            sk: signer.get_secret_key(),
+            g: signer.get_generator(),
+        }
+    }
+
+    pub fn first_round<R: Rng>(&self, info: &[u8], rng: &mut R) -> (PBSSignerSecretRandomness<G1>, PBSSignerFirstRoundMessage<G1>) {
+        let u = G1::ScalarField::rand(rng);
+        let s = G1::ScalarField::rand(rng);
+        let d = G1::ScalarField::rand(rng);
+
+        // the z branch is simulated, the pk branch is answered in the second round
+        let a = self.g.mul(u);
+        let b = self.g.mul(s).add(info_element::<G1>(info).mul(d));
+
+        (PBSSignerSecretRandomness { u, s, d }, PBSSignerFirstRoundMessage { a, b })
+    }
+
+    pub fn second_round(&self,
+                        secret_randomness: PBSSignerSecretRandomness<G1>,
+                        m1: &PBSVerifierFirstRoundMessage<G1>,
+    ) -> PBSSignerSecondRoundMessage<G1> {
+        // c + d = e
+        let c = m1.e - secret_randomness.d;
+        let r = secret_randomness.u - c * self.sk.sk;
+
+        PBSSignerSecondRoundMessage { r, c, s: secret_randomness.s, d: secret_randomness.d }
+    }
+}
@@ -0,0 +1,47 @@
+#[cfg(test)]
+mod tests {
+    use ark_bn254::g1::Config;
+    use ark_bn254::Fr;
+    use rand::thread_rng;
+
+    use crate::blind_schnorr_signature::error::BlindError;
+    use crate::partially_blind_schnorr_signature::signer::PBSSigner;
+    use crate::partially_blind_schnorr_signature::verifier::PBSVerifier;
+    use crate::schnorr_signature::key::{generate_key_pair, PublicKey, SecretKey};
+    use crate::schnorr_signature::signer::Signer;
+    use crate::schnorr_signature::verifier::Verifier;
+
+    #[test]
+    fn test_partially_blind_signature() {
+        let info = b"expiry=2026-12-31;denomination=10";
+        let message = [0u8, 1u8, 2u8, 3u8];
+        let (sk, pk): (SecretKey<Config>, PublicKey<Config>) = generate_key_pair(&mut thread_rng());
+
+        // partially blind signer/verifier
+        let pbs_signer = PBSSigner::new(&Signer::new(sk));
+        let pbs_verifier = PBSVerifier::new(&Verifier::new(pk));
+
+        // interaction
+        let (signer_secret_randomness, m1) = pbs_signer.first_round(info, &mut thread_rng());
+        let (verifier_secret_randomness, m2) = pbs_verifier.first_round(&m1, info, &message, &mut thread_rng());
+        let m3 = pbs_signer.second_round(signer_secret_randomness, &m2);
+        let signature = pbs_verifier.second_round(&verifier_secret_randomness, &m1, &m2, &m3).unwrap();
+
+        assert!(pbs_verifier.verify(info, &message, &signature));
+
+        // the signature is bound to both the info and the message
+        assert!(!pbs_verifier.verify(b"expiry=2027-12-31;denomination=10", &message, &signature));
+        assert!(!pbs_verifier.verify(info, &[0u8, 1u8, 2u8, 4u8], &signature));
+
+        // the user rejects a tampered answer
+        let mut m3 = m3;
+        m3.r += Fr::from(1u8);
+        assert_eq!(pbs_verifier.second_round(&verifier_secret_randomness, &m1, &m2, &m3).err(), Some(BlindError::InvalidResponse));
+
+        // or an answer from a signer that committed under different info
+        let (signer_secret_randomness, m1) = pbs_signer.first_round(b"expiry=2027-12-31;denomination=10", &mut thread_rng());
+        let (verifier_secret_randomness, m2) = pbs_verifier.first_round(&m1, info, &message, &mut thread_rng());
+        let m3 = pbs_signer.second_round(signer_secret_randomness, &m2);
+        assert_eq!(pbs_verifier.second_round(&verifier_secret_randomness, &m1, &m2, &m3).err(), Some(BlindError::InvalidResponse));
+    }
+}
@@ -0,0 +1,120 @@
+use std::ops::{Add, Mul};
+
+use ark_ec::short_weierstrass::{Projective, SWCurveConfig};
+use ark_ff::PrimeField;
+use ark_std::UniformRand;
+use rand::Rng;
+
+use crate::blind_schnorr_signature::error::BlindError;
+use crate::partially_blind_schnorr_signature::signature::{challenge, info_element, PartiallyBlindSignature};
+use crate::partially_blind_schnorr_signature::signer::{PBSSignerFirstRoundMessage, PBSSignerSecondRoundMessage};
+use crate::schnorr_signature::key::PublicKey;
+use crate::schnorr_signature::verifier::Verifier;
+
+pub struct PBSVerifier<G1>
+where
+    G1: SWCurveConfig + Clone,
+    G1::ScalarField: PrimeField,
+{
+    pub pk: PublicKey<G1>,
+    pub g: Projective<G1>,
+}
+
+/// Blinding factors of one session, with the info and message they were used for
+pub struct PBSVerifierSecretRandomness<G1>
+where
+    G1: SWCurveConfig + Clone,
+    G1::ScalarField: PrimeField,
+{
+    pub t1: G1::ScalarField,
+    pub t2: G1::ScalarField,
+    pub t3: G1::ScalarField,
+    pub t4: G1::ScalarField,
+    pub info: Vec<u8>,
+    pub message: Vec<u8>,
+}
+
+pub struct PBSVerifierFirstRoundMessage<G1>
+where
+    G1: SWCurveConfig + Clone,
+    G1::ScalarField: PrimeField,
+{
+    pub e: G1::ScalarField,
+}
+
+impl<G1> PBSVerifier<G1>
+where
+    G1: SWCurveConfig + Clone,
+    G1::ScalarField: PrimeField,
+    G1::BaseField: PrimeField,
+{
+    pub fn new(verifier: &Verifier<G1>) -> Self {
+        PBSVerifier {
+            pk: verifier.pk.clone(),
+            g: verifier.get_generator(),
+        }
+    }
+
+    pub fn first_round<R: Rng>(&self,
+                               m1: &PBSSignerFirstRoundMessage<G1>,
+                               info: &[u8],
+                               message: &[u8],
+                               rng: &mut R,
+    ) -> (PBSVerifierSecretRandomness<G1>, PBSVerifierFirstRoundMessage<G1>) {
+        let t1 = G1::ScalarField::rand(rng);
+        let t2 = G1::ScalarField::rand(rng);
+        let t3 = G1::ScalarField::rand(rng);
+        let t4 = G1::ScalarField::rand(rng);
+        let z = info_element::<G1>(info);
+
+        // alpha = a * g^{t1} * pk^{t2}, beta = b * g^{t3} * z^{t4}
+        let alpha = m1.a.add(self.g.mul(t1)).add(self.pk.pk.mul(t2));
+        let beta = m1.b.add(self.g.mul(t3)).add(z.mul(t4));
+
+        let e = challenge(&alpha, &beta, &z, message) - t2 - t4;
+
+        let (info, message) = (info.to_vec(), message.to_vec());
+        (PBSVerifierSecretRandomness { t1, t2, t3, t4, info, message }, PBSVerifierFirstRoundMessage { e })
+    }
+
+    /// Unblind the signer's answer into a signature on the (info, message) of the first round,
+    /// which is checked before it is returned
+    pub fn second_round(&self,
+                        secret_randomness: &PBSVerifierSecretRandomness<G1>,
+                        m1: &PBSSignerFirstRoundMessage<G1>,
+                        m2: &PBSVerifierFirstRoundMessage<G1>,
+                        m3: &PBSSignerSecondRoundMessage<G1>,
+    ) -> Result<PartiallyBlindSignature<G1>, BlindError> {
+        let (info, message) = (&secret_randomness.info, &secret_randomness.message);
+        let z = info_element::<G1>(info);
+
+        // a == g^r * pk^c, b == g^s * z^d, c + d == e
+        if m3.c + m3.d != m2.e
+            || m1.a != self.g.mul(m3.r).add(self.pk.pk.mul(m3.c))
+            || m1.b != self.g.mul(m3.s).add(z.mul(m3.d)) {
+            return Err(BlindError::InvalidResponse);
+        }
+
+        let signature = PartiallyBlindSignature {
+            rho: m3.r + secret_randomness.t1,
+            omega: m3.c + secret_randomness.t2,
+            sigma: m3.s + secret_randomness.t3,
+            delta: m3.d + secret_randomness.t4,
+        };
+
+        if !self.verify(info, message, &signature) {
+            return Err(BlindError::InvalidSignature);
+        }
+
+        Ok(signature)
+    }
+
+    /// omega + delta == H(g^{rho} * pk^{omega} || g^{sigma} * z^{delta} || z || message)
+    pub fn verify(&self, info: &[u8], message: &[u8], signature: &PartiallyBlindSignature<G1>) -> bool {
+        let z = info_element::<G1>(info);
+        let alpha = self.g.mul(signature.rho).add(self.pk.pk.mul(signature.omega));
+        let beta = self.g.mul(signature.sigma).add(z.mul(signature.delta));
+
+        signature.omega + signature.delta == challenge(&alpha, &beta, &z, message)
+    }
+}
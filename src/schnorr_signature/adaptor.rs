@@ -0,0 +1,30 @@
+use ark_ec::short_weierstrass::SWCurveConfig;
+use ark_ff::PrimeField;
+
+use crate::schnorr_signature::signature::{PreSignature, Signature};
+
+/// Complete a pre-signature with the witness t, i.e. (R', s' + t)
+pub fn adapt<G1>(pre_signature: &PreSignature<G1>, t: G1::ScalarField) -> Signature<G1>
+where
+    G1: SWCurveConfig + Clone,
+    G1::ScalarField: PrimeField,
+{
+    Signature {
+        r_g: pre_signature.r_g,
+        s: pre_signature.s + t,
+    }
+}
+
+/// Extract the witness t = s - s' from a pre-signature and its completed signature,
+/// returns None if the two do not share the same nonce R'
+pub fn extract<G1>(pre_signature: &PreSignature<G1>, signature: &Signature<G1>) -> Option<G1::ScalarField>
+where
+    G1: SWCurveConfig + Clone,
+    G1::ScalarField: PrimeField,
+{
+    if pre_signature.r_g != signature.r_g {
+        return None;
+    }
+
+    Some(signature.s - pre_signature.s)
+}
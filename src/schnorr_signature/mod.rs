@@ -3,4 +3,5 @@ pub mod signature;
 pub mod util;
 pub mod signer;
 pub mod verifier;
+pub mod adaptor;
 mod test;
\ No newline at end of file
@@ -2,6 +2,7 @@ use ark_ec::short_weierstrass::{Projective, SWCurveConfig};
 use ark_ff::PrimeField;
 
 /// Signature structure
+#[derive(Clone)]
 pub struct Signature<G1>
 where
     G1: SWCurveConfig + Clone,
@@ -13,3 +14,16 @@ where
     /// s = r + H(R, m) * sk
     pub s: G1::ScalarField,
 }
+
+/// Adaptor pre-signature structure, locked to a statement T = g^t
+pub struct PreSignature<G1>
+where
+    G1: SWCurveConfig + Clone,
+    G1::ScalarField: PrimeField,
+{
+    /// R' = g^r * T
+    pub r_g: Projective<G1>,
+
+    /// s' = r + H(R', m) * sk
+    pub s: G1::ScalarField,
+}
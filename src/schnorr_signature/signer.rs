@@ -7,7 +7,7 @@ use ark_std::UniformRand;
 use rand::Rng;
 use crate::hash::Hash256;
 use crate::schnorr_signature::key::SecretKey;
-use crate::schnorr_signature::signature::Signature;
+use crate::schnorr_signature::signature::{PreSignature, Signature};
 use crate::schnorr_signature::util::group_element_into_bytes;
 
 pub struct Signer<G1>
@@ -38,14 +38,14 @@ where
     G1::ScalarField: PrimeField,
 {
     pub(crate) fn get_generator(&self) -> Projective<G1> {
-        self.g.clone()
+        self.g
     }
 
     pub(crate) fn get_secret_key(&self) -> SecretKey<G1> {
         self.sk.clone()
     }
 
-    pub(crate) fn sign<R: Rng>(&self, message: &Vec<u8>, rng: &mut R) -> Signature<G1>
+    pub fn sign<R: Rng>(&self, message: &Vec<u8>, rng: &mut R) -> Signature<G1>
     where
         <G1 as CurveConfig>::BaseField: PrimeField,
     {
@@ -65,4 +65,26 @@ where
 
         Signature { s, r_g }
     }
+
+    /// Pre-sign a message under the statement T = g^t, without knowing t
+    pub fn pre_sign<R: Rng>(&self, message: &[u8], t_g: &Projective<G1>, rng: &mut R) -> PreSignature<G1>
+    where
+        <G1 as CurveConfig>::BaseField: PrimeField,
+    {
+        // Random nonce
+        let r = G1::ScalarField::rand(rng);
+        // R' = g^r * T
+        let r_g = self.get_generator().mul(r) + t_g;
+
+        let c: G1::ScalarField = {
+            let mut bytes = group_element_into_bytes::<G1>(&r_g);
+            bytes.extend_from_slice(message);
+            Hash256::hash_bytes(&bytes)
+        };
+
+        // Compute s' = r + c * sk
+        let s = r + c * self.sk.sk;
+
+        PreSignature { r_g, s }
+    }
 }
\ No newline at end of file
@@ -1,12 +1,19 @@
 #[cfg(test)]
 mod tests {
+    use std::ops::Mul;
+
+    use ark_ec::Group;
+    use ark_ec::short_weierstrass::Projective;
+    use ark_std::UniformRand;
     use rand::thread_rng;
 
+    use crate::schnorr_signature::adaptor::{adapt, extract};
     use crate::schnorr_signature::key::{generate_key_pair, PublicKey, SecretKey};
     use crate::schnorr_signature::signer::{Signer};
     use crate::schnorr_signature::verifier::{Verifier};
 
     use ark_bn254::g1::Config;
+    use ark_bn254::Fr;
 
     #[test]
     fn test_schnorr_signature() {
@@ -17,4 +24,54 @@ mod tests {
         let sig = signer.sign(&message.to_vec(), &mut thread_rng());
         assert!(verifier.verify(&message.to_vec(), &sig));
     }
+
+    #[test]
+    fn test_adaptor_signature() {
+        let message = [0u8, 1u8, 2u8, 3u8];
+        let (sk, pk): (SecretKey<Config>, PublicKey<Config>) = generate_key_pair(&mut thread_rng());
+        let signer = Signer::new(sk);
+        let verifier = Verifier::new(pk);
+
+        let t = Fr::rand(&mut thread_rng());
+        let t_g = Projective::<Config>::generator().mul(t);
+
+        let pre_sig = signer.pre_sign(&message, &t_g, &mut thread_rng());
+        assert!(verifier.pre_verify(&message, &t_g, &pre_sig));
+        // the pre-signature alone is not a valid signature
+        assert!(!verifier.verify(&message.to_vec(), &adapt(&pre_sig, Fr::from(0u8))));
+
+        let sig = adapt(&pre_sig, t);
+        assert!(verifier.verify(&message.to_vec(), &sig));
+        assert_eq!(extract(&pre_sig, &sig), Some(t));
+    }
+
+    #[test]
+    fn test_adaptor_signature_wrong_statement() {
+        let message = [0u8, 1u8, 2u8, 3u8];
+        let (sk, pk): (SecretKey<Config>, PublicKey<Config>) = generate_key_pair(&mut thread_rng());
+        let signer = Signer::new(sk);
+        let verifier = Verifier::new(pk);
+
+        let t_g = Projective::<Config>::generator().mul(Fr::rand(&mut thread_rng()));
+        let other_t_g = Projective::<Config>::generator().mul(Fr::rand(&mut thread_rng()));
+
+        let pre_sig = signer.pre_sign(&message, &t_g, &mut thread_rng());
+        assert!(!verifier.pre_verify(&message, &other_t_g, &pre_sig));
+        assert!(!verifier.pre_verify(&[3u8, 2u8, 1u8, 0u8], &t_g, &pre_sig));
+    }
+
+    #[test]
+    fn test_batch_verify() {
+        let message: Vec<Vec<u8>> = (0u8..8u8).map(|i| [i, 1u8, 2u8, 3u8].to_vec()).collect();
+        let (sk, pk): (SecretKey<Config>, PublicKey<Config>) = generate_key_pair(&mut thread_rng());
+        let signer = Signer::new(sk);
+        let verifier = Verifier::new(pk);
+
+        let mut signatures: Vec<_> = message.iter().map(|m| signer.sign(m, &mut thread_rng())).collect();
+        assert!(verifier.batch_verify(&message, &signatures));
+        assert!(!verifier.batch_verify(&message[1..], &signatures));
+
+        signatures[3].s += Fr::from(1u8);
+        assert!(!verifier.batch_verify(&message, &signatures));
+    }
 }
@@ -1,6 +1,8 @@
+use std::sync::atomic::{compiler_fence, Ordering};
+
 use ark_ec::{AffineRepr, CurveGroup};
-use ark_ec::short_weierstrass::{Projective, SWCurveConfig};
-use ark_ff::{BigInteger, PrimeField};
+use ark_ec::short_weierstrass::{Affine, Projective, SWCurveConfig};
+use ark_ff::{BigInteger, PrimeField, Zero};
 
 pub fn group_element_into_bytes<G1>(g: &Projective<G1>) -> Vec<u8>
 where
@@ -8,7 +10,32 @@ where
     G1::ScalarField: PrimeField,
     G1::BaseField: PrimeField,
 {
-    let mut res = g.into_affine().x().unwrap().into_bigint().to_bytes_le();
-    res.extend(g.into_affine().y().unwrap().into_bigint().to_bytes_le());
+    affine_into_bytes(&g.into_affine())
+}
+
+/// Same encoding as group_element_into_bytes, for points that are already normalized.
+/// The identity has no coordinates and is encoded as (0, 0), which is not on a curve with b != 0
+pub fn affine_into_bytes<G1>(g: &Affine<G1>) -> Vec<u8>
+where
+    G1: SWCurveConfig + Clone,
+    G1::ScalarField: PrimeField,
+    G1::BaseField: PrimeField,
+{
+    let zero = G1::BaseField::zero();
+    let (x, y) = g.xy().unwrap_or((&zero, &zero));
+    let mut res = x.into_bigint().to_bytes_le();
+    res.extend(y.into_bigint().to_bytes_le());
     res
-}
\ No newline at end of file
+}
+
+pub fn scalar_into_bytes<F: PrimeField>(f: &F) -> Vec<u8> {
+    f.into_bigint().to_bytes_le()
+}
+
+/// Overwrite a secret scalar with zero through a volatile write, so the store is not
+/// removed as dead when the scalar is dropped right after
+pub fn erase_scalar<F: PrimeField>(f: &mut F) {
+    // SAFETY: the pointer comes from a live mutable reference, so it is valid and aligned
+    unsafe { std::ptr::write_volatile(f, F::zero()) };
+    compiler_fence(Ordering::SeqCst);
+}
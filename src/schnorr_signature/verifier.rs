@@ -1,13 +1,14 @@
 use std::ops::Mul;
 
-use ark_ec::{CurveConfig, Group};
+use ark_ec::{CurveConfig, CurveGroup, Group, VariableBaseMSM};
 use ark_ec::short_weierstrass::{Projective, SWCurveConfig};
 use ark_ff::PrimeField;
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
 
 use crate::hash::Hash256;
 use crate::schnorr_signature::key::PublicKey;
-use crate::schnorr_signature::signature::Signature;
-use crate::schnorr_signature::util::group_element_into_bytes;
+use crate::schnorr_signature::signature::{PreSignature, Signature};
+use crate::schnorr_signature::util::{group_element_into_bytes, scalar_into_bytes};
 
 pub struct Verifier<G1>
 where
@@ -37,11 +38,11 @@ where
     G1::ScalarField: PrimeField,
 {
     pub fn get_generator(&self) -> Projective<G1> {
-        self.g.clone()
+        self.g
     }
 
     pub fn get_public_key(&self) -> Projective<G1> {
-        self.pk.pk.clone()
+        self.pk.pk
     }
 
     pub fn verify(&self, message: &Vec<u8>, signature: &Signature<G1>) -> bool
@@ -56,8 +57,64 @@ where
         };
 
         // Check if the recomputed e' matches the provided e
-        self.g.clone().mul(signature.s) == {
+        self.g.mul(signature.s) == {
             signature.r_g + self.get_public_key().mul(c)
         }
     }
+
+    /// Check that a pre-signature completes to a valid signature once t = log_g(T) is known
+    pub fn pre_verify(&self, message: &[u8], t_g: &Projective<G1>, pre_signature: &PreSignature<G1>) -> bool
+    where
+        <G1 as CurveConfig>::BaseField: PrimeField,
+    {
+        let c: G1::ScalarField = {
+            let mut bytes = group_element_into_bytes::<G1>(&pre_signature.r_g);
+            bytes.extend_from_slice(message);
+            Hash256::hash_bytes(&bytes)
+        };
+
+        // g^s' == R' / T * pk^c
+        self.g.mul(pre_signature.s) == {
+            pre_signature.r_g - t_g + self.get_public_key().mul(c)
+        }
+    }
+
+    /// Check n signatures at once with a random linear combination,
+    /// g^{sum rho_i s_i} == prod R_i^{rho_i} * pk^{sum rho_i c_i}
+    pub fn batch_verify(&self, message: &[Vec<u8>], signatures: &[Signature<G1>]) -> bool
+    where
+        <G1 as CurveConfig>::BaseField: PrimeField,
+    {
+        let n = message.len();
+        if signatures.len() != n {
+            return false;
+        }
+
+        let vec_c: Vec<G1::ScalarField> = (0..n).into_par_iter().map(|i| {
+            let mut bytes = group_element_into_bytes::<G1>(&signatures[i].r_g);
+            bytes.extend_from_slice(&message[i]);
+            Hash256::hash_bytes(bytes.as_slice())
+        }).collect();
+
+        // the weights are derived from everything being verified
+        let seed = {
+            let mut bytes = Vec::new();
+            for (signature, c_i) in signatures.iter().zip(&vec_c) {
+                bytes.extend(scalar_into_bytes(c_i));
+                bytes.extend(scalar_into_bytes(&signature.s));
+            }
+            Hash256::digest(&bytes)
+        };
+        let rho: Vec<G1::ScalarField> = (0..n as u64).into_par_iter().map(|i| {
+            let mut bytes = seed.to_vec();
+            bytes.extend(i.to_le_bytes());
+            Hash256::hash_bytes(bytes.as_slice())
+        }).collect();
+
+        let s: G1::ScalarField = signatures.iter().zip(&rho).map(|(signature, rho_i)| signature.s * rho_i).sum();
+        let c: G1::ScalarField = vec_c.iter().zip(&rho).map(|(c_i, rho_i)| *c_i * rho_i).sum();
+        let r_g: Vec<Projective<G1>> = signatures.iter().map(|signature| signature.r_g).collect();
+
+        self.g.mul(s) == Projective::msm_unchecked(&Projective::normalize_batch(&r_g), &rho) + self.get_public_key().mul(c)
+    }
 }
@@ -0,0 +1,333 @@
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+
+use ark_ec::short_weierstrass::SWCurveConfig;
+use ark_ff::PrimeField;
+use rand::Rng;
+
+use crate::blind_fse::session::BFDESignerAwaitingPayment;
+use crate::blind_fse::signer::{BFDESigner, BFDESignerFirstRoundMessage, BFDESignerSecondRoundMessage, BFDESignerSecretRandomness};
+use crate::blind_fse::verifier::BFDEVerifierFirstRoundMessage;
+use crate::blind_schnorr_signature::signer::{BSSigner, BSSignerFirstRoundMessage, BSSignerSecondRoundMessage, BSSignerSecretRandomness};
+use crate::blind_schnorr_signature::verifier::BSVerifierFirstRoundMessage;
+use crate::clock::Clock;
+use crate::fse::error::FseError;
+
+pub type SessionId = u64;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SessionError {
+    /// The client already holds the maximum number of open sessions
+    TooManySessions(String),
+    /// No open session with this id for this client, it may already have been answered
+    UnknownSession(SessionId),
+    /// The session timed out before the challenge arrived
+    Expired(SessionId),
+    /// The signer refused the challenge, the session is closed all the same
+    Rejected(FseError),
+}
+
+impl fmt::Display for SessionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SessionError::TooManySessions(client) => write!(f, "client {} has too many open sessions", client),
+            SessionError::UnknownSession(id) => write!(f, "unknown session {}", id),
+            SessionError::Expired(id) => write!(f, "session {} has expired", id),
+            SessionError::Rejected(e) => write!(f, "challenge rejected: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for SessionError {}
+
+impl From<FseError> for SessionError {
+    fn from(e: FseError) -> Self {
+        SessionError::Rejected(e)
+    }
+}
+
+/// Signer of a two-move blind protocol, whose first round leaves secret randomness behind.
+/// The randomness is expected to erase itself when dropped
+pub trait SessionSigner {
+    type SecretRandomness;
+    type FirstRoundMessage;
+    type Challenge;
+    type Response;
+
+    fn commit<R: Rng>(&self, rng: &mut R) -> (Self::SecretRandomness, Self::FirstRoundMessage);
+
+    /// Answer the challenge, consuming the randomness so it cannot answer another one
+    fn respond<R: Rng>(&self, secret_randomness: Self::SecretRandomness, challenge: &Self::Challenge, rng: &mut R) -> Result<Self::Response, SessionError>;
+}
+
+impl<G1> SessionSigner for BSSigner<G1>
+where
+    G1: SWCurveConfig + Clone,
+    G1::ScalarField: PrimeField,
+{
+    type SecretRandomness = BSSignerSecretRandomness<G1>;
+    type FirstRoundMessage = BSSignerFirstRoundMessage<G1>;
+    type Challenge = BSVerifierFirstRoundMessage<G1>;
+    type Response = BSSignerSecondRoundMessage<G1>;
+
+    fn commit<R: Rng>(&self, rng: &mut R) -> (Self::SecretRandomness, Self::FirstRoundMessage) {
+        self.first_round(rng)
+    }
+
+    fn respond<R: Rng>(&self, secret_randomness: Self::SecretRandomness, challenge: &Self::Challenge, _rng: &mut R) -> Result<Self::Response, SessionError> {
+        Ok(self.second_round(secret_randomness, challenge))
+    }
+}
+
+impl<G1> SessionSigner for BFDESigner<G1>
+where
+    G1: SWCurveConfig + Clone,
+    G1::ScalarField: PrimeField,
+{
+    type SecretRandomness = BFDESignerSecretRandomness<G1>;
+    type FirstRoundMessage = BFDESignerFirstRoundMessage<G1>;
+    type Challenge = BFDEVerifierFirstRoundMessage<G1>;
+    /// k, held until it is paid for, and the second round message
+    type Response = (BFDESignerAwaitingPayment<G1>, BFDESignerSecondRoundMessage<G1>);
+
+    fn commit<R: Rng>(&self, rng: &mut R) -> (Self::SecretRandomness, Self::FirstRoundMessage) {
+        self.first_round(rng)
+    }
+
+    fn respond<R: Rng>(&self, secret_randomness: Self::SecretRandomness, challenge: &Self::Challenge, rng: &mut R) -> Result<Self::Response, SessionError> {
+        let (k, m2) = self.second_round(secret_randomness, challenge, rng)?;
+        Ok((BFDESignerAwaitingPayment::new(k), m2))
+    }
+}
+
+struct Session<S: SessionSigner> {
+    client: String,
+    expiry: u64,
+    secret_randomness: S::SecretRandomness,
+}
+
+/// Signer-side store of open sessions. Every session answers at most one challenge, expires
+/// `ttl` seconds after it was opened, and each client holds at most `max_per_client` of them
+pub struct SessionStore<S, C>
+where
+    S: SessionSigner,
+    C: Clock,
+{
+    signer: S,
+    clock: C,
+    ttl: u64,
+    max_per_client: usize,
+    next_id: SessionId,
+    sessions: HashMap<SessionId, Session<S>>,
+    /// Open sessions ordered by deadline, so purging only looks at the expired ones
+    expiries: BTreeSet<(u64, SessionId)>,
+    /// Open sessions per client, clients without any are not kept
+    counts: HashMap<String, usize>,
+}
+
+impl<S, C> SessionStore<S, C>
+where
+    S: SessionSigner,
+    C: Clock,
+{
+    pub fn new(signer: S, clock: C, ttl: u64, max_per_client: usize) -> Self {
+        SessionStore {
+            signer,
+            clock,
+            ttl,
+            max_per_client,
+            next_id: 0,
+            sessions: HashMap::new(),
+            expiries: BTreeSet::new(),
+            counts: HashMap::new(),
+        }
+    }
+
+    pub fn signer(&self) -> &S {
+        &self.signer
+    }
+
+    pub fn clock(&self) -> &C {
+        &self.clock
+    }
+
+    /// Number of sessions still held, expired ones included until they are purged
+    pub fn len(&self) -> usize {
+        self.sessions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sessions.is_empty()
+    }
+
+    pub fn open_sessions(&self, client: &str) -> usize {
+        self.counts.get(client).copied().unwrap_or(0)
+    }
+
+    /// Remove a session from the store and its indices, its nonces are erased once dropped
+    fn close(&mut self, id: SessionId) -> Option<Session<S>> {
+        let session = self.sessions.remove(&id)?;
+        self.expiries.remove(&(session.expiry, id));
+        if let Some(count) = self.counts.get_mut(&session.client) {
+            *count -= 1;
+            if *count == 0 {
+                self.counts.remove(&session.client);
+            }
+        }
+        Some(session)
+    }
+
+    /// Run the signer's first round for `client` and keep its nonces under a fresh id
+    pub fn open<R: Rng>(&mut self, client: &str, rng: &mut R) -> Result<(SessionId, S::FirstRoundMessage), SessionError> {
+        self.purge_expired();
+        if self.open_sessions(client) >= self.max_per_client {
+            return Err(SessionError::TooManySessions(client.to_string()));
+        }
+
+        let (secret_randomness, m1) = self.signer.commit(rng);
+        let id = self.next_id;
+        self.next_id += 1;
+        let expiry = self.clock.now().saturating_add(self.ttl);
+        self.sessions.insert(id, Session {
+            client: client.to_string(),
+            expiry,
+            secret_randomness,
+        });
+        self.expiries.insert((expiry, id));
+        *self.counts.entry(client.to_string()).or_insert(0) += 1;
+
+        Ok((id, m1))
+    }
+
+    /// Answer the challenge of an open session, which is closed and its nonces erased
+    /// whatever the outcome
+    pub fn respond<R: Rng>(&mut self, id: SessionId, client: &str, challenge: &S::Challenge, rng: &mut R) -> Result<S::Response, SessionError> {
+        // another client cannot close the session, nor learn that it exists
+        if self.sessions.get(&id).is_none_or(|session| session.client != client) {
+            return Err(SessionError::UnknownSession(id));
+        }
+        let session = self.close(id).ok_or(SessionError::UnknownSession(id))?;
+
+        if self.clock.now() >= session.expiry {
+            return Err(SessionError::Expired(id));
+        }
+
+        self.signer.respond(session.secret_randomness, challenge, rng)
+    }
+
+    /// Close the sessions whose deadline has passed and erase their nonces, returns how many
+    pub fn purge_expired(&mut self) -> usize {
+        let now = self.clock.now();
+        let mut purged = 0;
+        while let Some(&(expiry, id)) = self.expiries.first() {
+            if expiry > now {
+                break;
+            }
+            self.close(id);
+            purged += 1;
+        }
+        purged
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_bn254::g1::Config;
+    use rand::thread_rng;
+
+    use crate::blind_fse::signer::BFDESigner;
+    use crate::blind_fse::verifier::BFDEVerifier;
+    use crate::blind_schnorr_signature::signer::BSSigner;
+    use crate::blind_schnorr_signature::verifier::BSVerifier;
+    use crate::clock::MockClock;
+    use crate::fse::error::FseError;
+    use crate::schnorr_signature::key::{generate_key_pair, PublicKey, SecretKey};
+    use crate::schnorr_signature::signer::Signer;
+    use crate::schnorr_signature::verifier::Verifier;
+    use crate::session_store::{SessionError, SessionStore};
+
+    fn setup() -> (SessionStore<BSSigner<Config>, MockClock>, BSVerifier<Config>, Verifier<Config>) {
+        let (sk, pk): (SecretKey<Config>, PublicKey<Config>) = generate_key_pair(&mut thread_rng());
+        let verifier = Verifier::new(pk);
+        let store = SessionStore::new(BSSigner::new(&Signer::new(sk)), MockClock::new(1_000), 60, 2);
+        (store, BSVerifier::new(&verifier), verifier)
+    }
+
+    #[test]
+    fn test_interleaved_sessions() {
+        let (mut store, bs_verifier, verifier) = setup();
+        let message = [0u8, 1u8, 2u8, 3u8];
+
+        let (id_a, m1_a) = store.open("alice", &mut thread_rng()).unwrap();
+        let (id_b, m1_b) = store.open("bob", &mut thread_rng()).unwrap();
+        let (secret_a, m2_a) = bs_verifier.first_round(&m1_a, message.to_vec(), &mut thread_rng());
+        let (secret_b, m2_b) = bs_verifier.first_round(&m1_b, message.to_vec(), &mut thread_rng());
+
+        // bob cannot answer alice's session
+        assert_eq!(store.respond(id_a, "bob", &m2_a, &mut thread_rng()).err(), Some(SessionError::UnknownSession(id_a)));
+
+        let m3_b = store.respond(id_b, "bob", &m2_b, &mut thread_rng()).unwrap();
+        let m3_a = store.respond(id_a, "alice", &m2_a, &mut thread_rng()).unwrap();
+        assert!(verifier.verify(&message.to_vec(), &bs_verifier.second_round(&secret_a, &m1_a, &m2_a, &m3_a).unwrap()));
+        assert!(verifier.verify(&message.to_vec(), &bs_verifier.second_round(&secret_b, &m1_b, &m2_b, &m3_b).unwrap()));
+
+        // each session answers once
+        assert_eq!(store.respond(id_a, "alice", &m2_b, &mut thread_rng()).err(), Some(SessionError::UnknownSession(id_a)));
+        assert!(store.is_empty());
+    }
+
+    #[test]
+    fn test_limits() {
+        let (mut store, bs_verifier, _) = setup();
+
+        let (id, m1) = store.open("alice", &mut thread_rng()).unwrap();
+        store.open("alice", &mut thread_rng()).unwrap();
+        assert_eq!(store.open("alice", &mut thread_rng()).err(), Some(SessionError::TooManySessions("alice".to_string())));
+        assert!(store.open("bob", &mut thread_rng()).is_ok());
+
+        // a late challenge is refused and its session closed
+        let (_, m2) = bs_verifier.first_round(&m1, vec![0u8], &mut thread_rng());
+        store.clock().advance(60);
+        assert_eq!(store.respond(id, "alice", &m2, &mut thread_rng()).err(), Some(SessionError::Expired(id)));
+        assert_eq!(store.purge_expired(), 2);
+        assert!(store.is_empty());
+        assert_eq!(store.open_sessions("alice"), 0);
+
+        // the expired sessions no longer count against the cap
+        assert!(store.open("alice", &mut thread_rng()).is_ok());
+        assert!(store.open("alice", &mut thread_rng()).is_ok());
+
+        // a ttl past the end of the clock never expires rather than overflowing
+        let mut store = SessionStore::new(BSSigner::new(&Signer::new(generate_key_pair(&mut thread_rng()).0)), MockClock::new(1_000), u64::MAX, 1);
+        let (id, m1) = store.open("alice", &mut thread_rng()).unwrap();
+        let (_, m2) = bs_verifier.first_round(&m1, vec![0u8], &mut thread_rng());
+        assert_eq!(store.purge_expired(), 0);
+        assert!(store.respond(id, "alice", &m2, &mut thread_rng()).is_ok());
+    }
+
+    #[test]
+    fn test_blind_fse_sessions() {
+        let message = vec![[0u8, 1u8, 2u8, 3u8].to_vec(); 2];
+        let (sk, pk): (SecretKey<Config>, PublicKey<Config>) = generate_key_pair(&mut thread_rng());
+        let verifier = Verifier::new(pk);
+        let fde_verifier = BFDEVerifier::new(&verifier, 2, 4);
+        let mut store = SessionStore::new(BFDESigner::new(&Signer::new(sk), 2, 4), MockClock::new(0), 60, 1);
+
+        let (id, m1) = store.open("alice", &mut thread_rng()).unwrap();
+        let (secret_randomness, m2) = fde_verifier.first_round(&m1, &message, &mut thread_rng()).unwrap();
+        let (payment, m3) = store.respond(id, "alice", &m2, &mut thread_rng()).unwrap();
+        assert!(fde_verifier.second_round(&m1, &m2, &m3));
+
+        let signatures = fde_verifier.recover(&secret_randomness, &m1, &m2, &m3, &message, payment.reveal_key()).unwrap();
+        assert!(verifier.batch_verify(&message, &signatures));
+        assert!(store.respond(id, "alice", &m2, &mut thread_rng()).is_err());
+
+        // a malformed challenge is refused by the signer and still closes the session
+        let (id, m1) = store.open("alice", &mut thread_rng()).unwrap();
+        let (_, mut m2) = fde_verifier.first_round(&m1, &message, &mut thread_rng()).unwrap();
+        m2.c.pop();
+        assert_eq!(store.respond(id, "alice", &m2, &mut thread_rng()).err(), Some(SessionError::Rejected(FseError::LengthMismatch)));
+        assert_eq!(store.open_sessions("alice"), 0);
+        assert!(store.is_empty());
+    }
+}